@@ -0,0 +1,132 @@
+//! Daily active-hours window (`--active-hours`)
+//!
+//! Outside the window the update loop keeps running but skips its updates.
+//! A window whose end is before its start (e.g. `22:00-06:00`) crosses
+//! midnight. Times are compared in local time, or at a fixed UTC offset given
+//! with `--timezone`.
+
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Local, NaiveTime, Utc};
+
+/// A daily window `start-end`; the start is inclusive, the end exclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl ActiveHours {
+    /// Parse `HH:MM-HH:MM`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (start, end) = spec
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("Active hours must be HH:MM-HH:MM, got {}", spec))?;
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|_| anyhow::anyhow!("Invalid time {:?} in active hours {}", time.trim(), spec))
+        };
+        let hours = Self { start: parse(start)?, end: parse(end)? };
+        if hours.start == hours.end {
+            return Err(anyhow::anyhow!("Active hours {} start and end at the same time", spec));
+        }
+        Ok(hours)
+    }
+
+    /// Whether `time` falls inside the window
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            // 跨越午夜，如 22:00-06:00
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// Whether the window is open at `now`, in `timezone` (local time when `None`)
+    pub fn is_active(&self, now: DateTime<Utc>, timezone: Option<FixedOffset>) -> bool {
+        let time = match timezone {
+            Some(offset) => now.with_timezone(&offset).time(),
+            None => now.with_timezone(&Local).time(),
+        };
+        self.contains(time)
+    }
+}
+
+impl std::fmt::Display for ActiveHours {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+/// Parse `--timezone`: `local`, `UTC` or a fixed offset such as `+08:00`; `None` means local time
+pub fn parse_timezone(spec: &str) -> Result<Option<FixedOffset>> {
+    match spec.trim() {
+        "" | "local" => Ok(None),
+        "UTC" | "utc" | "Z" => Ok(FixedOffset::east_opt(0)),
+        offset => {
+            let invalid = || anyhow::anyhow!("Timezone must be local, UTC or an offset like +08:00, got {}", spec);
+            let (sign, rest) = match offset.as_bytes().first() {
+                Some(b'+') => (1, &offset[1..]),
+                Some(b'-') => (-1, &offset[1..]),
+                _ => return Err(invalid()),
+            };
+            let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+            let (hours, minutes): (i32, i32) = (hours.parse().map_err(|_| invalid())?, minutes.parse().map_err(|_| invalid())?);
+            if hours > 14 || !(0..60).contains(&minutes) {
+                return Err(invalid());
+            }
+            FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(Some).ok_or_else(invalid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(spec: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(spec, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_contains() {
+        let day = ActiveHours::parse("08:00-23:00").unwrap();
+        assert!(day.contains(time("08:00")));
+        assert!(day.contains(time("12:30")));
+        assert!(!day.contains(time("23:00")));
+        assert!(!day.contains(time("03:00")));
+        assert_eq!(day.to_string(), "08:00-23:00");
+
+        // 跨越午夜
+        let night = ActiveHours::parse("22:00-06:00").unwrap();
+        assert!(night.contains(time("23:30")));
+        assert!(night.contains(time("00:00")));
+        assert!(night.contains(time("05:59")));
+        assert!(!night.contains(time("06:00")));
+        assert!(!night.contains(time("12:00")));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(ActiveHours::parse("8:00-23:00").is_ok());
+        assert!(ActiveHours::parse("08:00").is_err());
+        assert!(ActiveHours::parse("08:00-25:00").is_err());
+        assert!(ActiveHours::parse("08:00-08:00").is_err());
+
+        assert_eq!(parse_timezone("local").unwrap(), None);
+        assert_eq!(parse_timezone("UTC").unwrap(), FixedOffset::east_opt(0));
+        assert_eq!(parse_timezone("+08:00").unwrap(), FixedOffset::east_opt(8 * 3600));
+        assert_eq!(parse_timezone("-05:30").unwrap(), FixedOffset::west_opt(5 * 3600 + 1800));
+        assert!(parse_timezone("Asia/Shanghai").is_err());
+        assert!(parse_timezone("+25:00").is_err());
+    }
+
+    #[test]
+    fn test_is_active() {
+        let hours = ActiveHours::parse("08:00-23:00").unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-01-01T20:00:00Z").unwrap().with_timezone(&Utc);
+        assert!(hours.is_active(now, parse_timezone("UTC").unwrap()));
+        // UTC 20:00 在 +08:00 为次日 04:00
+        assert!(!hours.is_active(now, parse_timezone("+08:00").unwrap()));
+    }
+}
@@ -0,0 +1,84 @@
+//! CAA (certificate authority authorization) record data
+//!
+//! Cloudflare takes CAA records as a structured `data` object but reports
+//! them back as presentation-format `content` (`0 issue "letsencrypt.org"`),
+//! so both directions are handled here to compare and update records.
+
+use anyhow::Result;
+
+/// Property tags Cloudflare accepts for CAA records
+pub const TAGS: [&str; 3] = ["issue", "issuewild", "iodef"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaaData {
+    pub flags: u8,
+    pub tag: String,
+    pub value: String,
+}
+
+impl CaaData {
+    pub fn new(flags: u8, tag: &str, value: &str) -> Result<Self> {
+        if !TAGS.contains(&tag) {
+            return Err(anyhow::anyhow!("CAA tag must be one of {:?}, got {}", TAGS, tag));
+        }
+        if value.is_empty() {
+            return Err(anyhow::anyhow!("CAA value must not be empty"));
+        }
+        Ok(Self { flags, tag: tag.to_string(), value: value.to_string() })
+    }
+
+    /// Parse presentation format, e.g. `0 issue "letsencrypt.org"`
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut parts = content.trim().splitn(3, ' ');
+        let (Some(flags), Some(tag), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(anyhow::anyhow!("Invalid CAA content: {}", content));
+        };
+        let flags = flags
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid CAA flags in: {}", content))?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        Self::new(flags, tag, value)
+    }
+
+    /// Presentation format as reported in a record's `content`
+    pub fn content(&self) -> String {
+        format!("{} {} \"{}\"", self.flags, self.tag, self.value)
+    }
+
+    /// The `data` object for the Cloudflare API
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "flags": self.flags,
+            "tag": self.tag,
+            "value": self.value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_round_trip() {
+        let caa = CaaData::new(0, "issue", "letsencrypt.org").unwrap();
+        assert_eq!(caa.content(), "0 issue \"letsencrypt.org\"");
+        assert_eq!(CaaData::parse(&caa.content()).unwrap(), caa);
+
+        let caa = CaaData::parse("128 iodef \"mailto:security@example.com\"").unwrap();
+        assert_eq!(caa.flags, 128);
+        assert_eq!(caa.to_json()["value"], "mailto:security@example.com");
+    }
+
+    #[test]
+    fn test_invalid_caa() {
+        assert!(CaaData::new(0, "issuer", "letsencrypt.org").is_err());
+        assert!(CaaData::new(0, "issue", "").is_err());
+        assert!(CaaData::parse("issue \"letsencrypt.org\"").is_err());
+        assert!(CaaData::parse("256 issue \"letsencrypt.org\"").is_err());
+    }
+}
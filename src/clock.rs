@@ -0,0 +1,121 @@
+//! Time source for time-dependent logic
+//!
+//! Code that reads the time or sleeps goes through a [`Clock`] so tests can
+//! swap in a [`MockClock`] and advance time deterministically. The production
+//! [`SystemClock`] holds no state and forwards straight to `chrono` and
+//! `tokio`, so its monotonic time and sleeps also follow tokio's paused test
+//! time (`#[tokio::test(start_paused = true)]`).
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Wall-clock time, monotonic time and sleeping
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Current wall-clock time
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current monotonic time, for measuring intervals
+    fn instant(&self) -> Instant;
+
+    /// Wait for `duration`
+    fn sleep(&self, duration: Duration) -> tokio::time::Sleep;
+}
+
+/// The real clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    #[inline]
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    #[inline]
+    fn sleep(&self, duration: Duration) -> tokio::time::Sleep {
+        tokio::time::sleep(duration)
+    }
+}
+
+/// A shared clock, so a test can keep a handle to advance the clock it hands out
+impl<T: Clock + ?Sized> Clock for std::sync::Arc<T> {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+
+    fn instant(&self) -> Instant {
+        (**self).instant()
+    }
+
+    fn sleep(&self, duration: Duration) -> tokio::time::Sleep {
+        (**self).sleep(duration)
+    }
+}
+
+/// A clock that only moves when advanced; sleeping advances it instantly
+#[cfg(test)]
+#[derive(Debug)]
+pub struct MockClock {
+    start: (DateTime<Utc>, Instant),
+    elapsed: std::sync::Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { start: (now, Instant::now()), elapsed: std::sync::Mutex::new(Duration::ZERO) }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+
+    /// Total time advanced so far
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.start.0 + chrono::Duration::from_std(self.elapsed()).unwrap()
+    }
+
+    fn instant(&self) -> Instant {
+        self.start.1 + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> tokio::time::Sleep {
+        self.advance(duration);
+        tokio::time::sleep(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_clock() {
+        let start = DateTime::parse_from_rfc3339("2024-05-01T08:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = MockClock::new(start);
+        let instant = clock.instant();
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+        assert_eq!(clock.instant() - instant, Duration::from_secs(90));
+
+        // 睡眠立即返回并推进时间
+        clock.sleep(Duration::from_secs(3600)).await;
+        assert_eq!(clock.elapsed(), Duration::from_secs(3690));
+        assert_eq!(clock.now().to_rfc3339(), "2024-05-01T09:01:30+00:00");
+    }
+}
@@ -0,0 +1,60 @@
+//! Events for the `--tui` live dashboard
+//!
+//! The update loop reports progress here without knowing whether a dashboard
+//! is running; events are dropped unless one has been started (see `tui`).
+
+// 未启用 tui 特性时事件只发送、不消费
+#![cfg_attr(not(feature = "tui"), allow(dead_code))]
+
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+#[derive(Debug)]
+pub enum Event {
+    /// A line of progress or log output
+    Log(String),
+    /// The public IP detected this cycle
+    CurrentIp(String),
+    /// Result of the last update of a domain
+    Domain { name: String, status: String, ok: bool },
+    /// When the next scheduled update runs
+    NextUpdate(Instant),
+    /// Restore the terminal and stop rendering
+    Quit,
+}
+
+static SENDER: OnceLock<Mutex<Sender<Event>>> = OnceLock::new();
+
+/// Open the event channel; only the first caller gets the receiver
+pub fn channel() -> Option<Receiver<Event>> {
+    let (tx, rx) = mpsc::channel();
+    SENDER.set(Mutex::new(tx)).ok().map(|_| rx)
+}
+
+pub fn is_active() -> bool {
+    SENDER.get().is_some()
+}
+
+pub fn send(event: Event) {
+    if let Some(sender) = SENDER.get() {
+        let _ = sender.lock().unwrap().send(event);
+    }
+}
+
+/// Log target that forwards each written line to the dashboard
+pub struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for line in String::from_utf8_lossy(buf).lines().filter(|line| !line.is_empty()) {
+            send(Event::Log(line.to_string()));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
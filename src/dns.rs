@@ -0,0 +1,137 @@
+//! DNS lookups: public IP detection, propagation checks and CNAME targets
+//!
+//! IP detection queries special "whoami" names against resolvers that answer
+//! with the address of the client asking, which is faster than HTTP echo
+//! services and harder to block.
+
+use crate::clock::Clock;
+use anyhow::Result;
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use log::debug;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+/// OpenDNS resolvers answering `myip.opendns.com` over IPv4
+const OPENDNS_V4: [IpAddr; 2] = [
+    IpAddr::V4(Ipv4Addr::new(208, 67, 222, 222)),
+    IpAddr::V4(Ipv4Addr::new(208, 67, 220, 220)),
+];
+
+/// OpenDNS resolvers answering `myip.opendns.com` over IPv6
+const OPENDNS_V6: [IpAddr; 2] = [
+    IpAddr::V6(Ipv6Addr::new(0x2620, 0x119, 0x35, 0, 0, 0, 0, 0x35)),
+    IpAddr::V6(Ipv6Addr::new(0x2620, 0x119, 0x53, 0, 0, 0, 0, 0x53)),
+];
+
+/// Akamai authoritative server (ns1-1.akamaitech.net) answering `whoami.akamai.net`
+const AKAMAI_V4: [IpAddr; 1] = [IpAddr::V4(Ipv4Addr::new(193, 108, 88, 1))];
+
+/// Cloudflare public resolvers used to verify record propagation
+const CLOUDFLARE_PUBLIC: [IpAddr; 2] = [
+    IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)),
+    IpAddr::V4(Ipv4Addr::new(1, 0, 0, 1)),
+];
+
+/// Delay between propagation checks
+const PROPAGATION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Build a non-caching resolver that talks only to the given name servers
+pub fn resolver_for(servers: &[IpAddr]) -> TokioAsyncResolver {
+    let group = NameServerConfigGroup::from_ips_clear(servers, 53, true);
+    let mut opts = ResolverOpts::default();
+    opts.timeout = Duration::from_secs(3);
+    opts.attempts = 1;
+    opts.cache_size = 0;
+    TokioAsyncResolver::tokio(ResolverConfig::from_parts(None, vec![], group), opts)
+}
+
+/// Look up all A/AAAA addresses of `name` (depending on `record_type`)
+pub async fn lookup_addresses(resolver: &TokioAsyncResolver, name: &str, record_type: &str) -> Result<Vec<IpAddr>> {
+    let addresses = match record_type {
+        "AAAA" => resolver.ipv6_lookup(name).await?.iter().map(|r| IpAddr::V6(r.0)).collect(),
+        _ => resolver.ipv4_lookup(name).await?.iter().map(|r| IpAddr::V4(r.0)).collect(),
+    };
+    Ok(addresses)
+}
+
+/// Look up the first A/AAAA address of `name` (depending on `record_type`)
+pub async fn lookup_address(resolver: &TokioAsyncResolver, name: &str, record_type: &str) -> Result<String> {
+    lookup_addresses(resolver, name, record_type)
+        .await?
+        .first()
+        .map(|ip| ip.to_string())
+        .ok_or_else(|| anyhow::anyhow!("No {} record returned for {}", record_type, name))
+}
+
+/// Resolve `name` to its first IPv4 address through a public resolver (follows CNAME chains);
+/// used to check and flatten CNAME targets
+pub async fn resolve_target(name: &str) -> Result<String> {
+    lookup_address(&resolver_for(&CLOUDFLARE_PUBLIC), name, "A").await
+}
+
+/// Poll a public resolver until `name` resolves to `expected_ip` or `timeout` elapses on `clock`
+pub async fn wait_for_propagation(clock: &dyn Clock, name: &str, record_type: &str, expected_ip: &str, timeout: Duration) -> Result<()> {
+    let expected: IpAddr = expected_ip.parse()?;
+    let resolver = resolver_for(&CLOUDFLARE_PUBLIC);
+    let started = clock.instant();
+
+    loop {
+        let seen = match lookup_addresses(&resolver, name, record_type).await {
+            Ok(addresses) if addresses.contains(&expected) => return Ok(()),
+            Ok(addresses) => format!("{:?}", addresses),
+            Err(e) => e.to_string(),
+        };
+        debug!("{} not yet propagated (resolver answered: {})", name, seen);
+
+        let elapsed = clock.instant().saturating_duration_since(started);
+        if elapsed >= timeout {
+            return Err(anyhow::anyhow!(
+                "{} did not resolve to {} within {}s (last answer: {})",
+                name, expected_ip, timeout.as_secs(), seen
+            ));
+        }
+        clock.sleep(PROPAGATION_POLL_INTERVAL.min(timeout - elapsed)).await;
+    }
+}
+
+/// Look up A/AAAA addresses of `name` directly from the zone's authoritative `name_servers`
+/// (host names, resolved through a public resolver), bypassing every cache
+pub async fn lookup_authoritative(name_servers: &[String], name: &str, record_type: &str) -> Result<Vec<IpAddr>> {
+    let public = resolver_for(&CLOUDFLARE_PUBLIC);
+    let mut servers = Vec::new();
+    for name_server in name_servers {
+        match lookup_addresses(&public, name_server, "A").await {
+            Ok(addresses) => servers.extend(addresses),
+            Err(e) => debug!("Unable to resolve name server {}: {}", name_server, e),
+        }
+    }
+    if servers.is_empty() {
+        return Err(anyhow::anyhow!("Unable to resolve any name server of {}", name_servers.join(", ")));
+    }
+    lookup_addresses(&resolver_for(&servers), name, record_type).await
+}
+
+/// Get the public IP by asking OpenDNS (and Akamai for IPv4) who we are
+pub async fn get_public_ip(record_type: &str) -> Result<String> {
+    let providers: Vec<(&str, &[IpAddr])> = match record_type {
+        "AAAA" => vec![("myip.opendns.com.", &OPENDNS_V6)],
+        _ => vec![
+            ("myip.opendns.com.", &OPENDNS_V4),
+            ("whoami.akamai.net.", &AKAMAI_V4),
+        ],
+    };
+
+    for (name, servers) in providers {
+        let resolver = resolver_for(servers);
+        match lookup_address(&resolver, name, record_type).await {
+            Ok(ip) => {
+                debug!("Public IP {} detected via DNS query for {}", ip, name);
+                return Ok(ip);
+            }
+            Err(e) => debug!("DNS query for {} failed: {}", name, e),
+        }
+    }
+
+    Err(anyhow::anyhow!("Unable to obtain public IP via DNS"))
+}
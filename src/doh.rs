@@ -0,0 +1,173 @@
+//! DNS-over-HTTPS hostname resolution
+//!
+//! With `--doh-resolver`, the hostnames of the IP services and the Cloudflare
+//! API are resolved through a DoH server's JSON API instead of the system
+//! resolver, so a tampering local resolver cannot redirect them. A lookup the
+//! DoH server cannot answer falls back to system resolution with a warning.
+//! The resolver is only built with the `doh` feature; other builds validate
+//! the option and keep using the system resolver.
+
+use anyhow::Result;
+#[cfg(feature = "doh")]
+use hyper::client::connect::dns::Name;
+#[cfg(feature = "doh")]
+use log::debug;
+use log::warn;
+#[cfg(feature = "doh")]
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::Url;
+use std::net::IpAddr;
+#[cfg(feature = "doh")]
+use std::net::SocketAddr;
+#[cfg(feature = "doh")]
+use std::time::Duration;
+
+/// Whether this build can resolve through DoH
+pub const SUPPORTED: bool = cfg!(feature = "doh");
+
+/// DNS record type codes used in DoH JSON answers
+#[cfg_attr(not(feature = "doh"), allow(dead_code))]
+const TYPE_A: u64 = 1;
+#[cfg_attr(not(feature = "doh"), allow(dead_code))]
+const TYPE_AAAA: u64 = 28;
+
+/// Validate a DoH endpoint, e.g. `https://1.1.1.1/dns-query`
+pub fn parse_url(spec: &str) -> Result<Url> {
+    let url = Url::parse(spec).map_err(|e| anyhow::anyhow!("Invalid DoH resolver URL {}: {}", spec, e))?;
+    if url.scheme() != "https" {
+        return Err(anyhow::anyhow!("DoH resolver must be an https:// URL, got {}", spec));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(anyhow::anyhow!("DoH resolver URL has no host: {}", spec));
+    }
+    Ok(url)
+}
+
+/// Addresses of the given type in a DoH JSON response (`application/dns-json`)
+#[cfg_attr(not(feature = "doh"), allow(dead_code))]
+pub fn parse_answer(response: &serde_json::Value, record_type: u64) -> Result<Vec<IpAddr>> {
+    let status = response["Status"].as_u64().unwrap_or(u64::MAX);
+    // 3 = NXDOMAIN：名称不存在，与没有记录一样返回空
+    if status != 0 && status != 3 {
+        return Err(anyhow::anyhow!("DoH server returned DNS status {}", status));
+    }
+    Ok(response["Answer"]
+        .as_array()
+        .map(|answers| {
+            answers
+                .iter()
+                .filter(|answer| answer["type"].as_u64() == Some(record_type))
+                .filter_map(|answer| answer["data"].as_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Resolver for reqwest clients that queries a DoH server
+#[cfg(feature = "doh")]
+#[derive(Clone)]
+pub struct DohResolver {
+    url: Url,
+    // 访问 DoH 服务器本身使用系统解析（使用 IP 形式的 URL 可完全绕过本地解析）
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "doh")]
+impl DohResolver {
+    pub fn new(url: Url) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        Self { url, client }
+    }
+
+    async fn query(&self, name: &str, record_type: u64) -> Result<Vec<IpAddr>> {
+        let type_name = if record_type == TYPE_AAAA { "AAAA" } else { "A" };
+        let response: serde_json::Value = self
+            .client
+            .get(self.url.clone())
+            .query(&[("name", name), ("type", type_name)])
+            .header(reqwest::header::ACCEPT, "application/dns-json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        parse_answer(&response, record_type)
+    }
+
+    /// All A and AAAA addresses of `name`
+    pub async fn lookup(&self, name: &str) -> Result<Vec<IpAddr>> {
+        let (v4, v6) = tokio::join!(self.query(name, TYPE_A), self.query(name, TYPE_AAAA));
+        let addresses: Vec<IpAddr> = match (v4, v6) {
+            (Err(e), Err(_)) => return Err(e),
+            (v4, v6) => v4.unwrap_or_default().into_iter().chain(v6.unwrap_or_default()).collect(),
+        };
+        if addresses.is_empty() {
+            return Err(anyhow::anyhow!("DoH server has no address for {}", name));
+        }
+        Ok(addresses)
+    }
+}
+
+#[cfg(feature = "doh")]
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let host = name.as_str();
+            // 端口由连接器按 URL 填写，这里只返回地址
+            let addresses: Vec<SocketAddr> = match resolver.lookup(host).await {
+                Ok(addresses) => {
+                    debug!("DoH resolved {} to {:?}", host, addresses);
+                    addresses.into_iter().map(|ip| SocketAddr::new(ip, 0)).collect()
+                }
+                Err(e) => {
+                    warn!("⚠️ DoH lookup of {} via {} failed, using system resolver: {}", host, resolver.url, e);
+                    tokio::net::lookup_host((host, 0)).await?.collect()
+                }
+            };
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Warn (once) that this build resolves through the system resolver despite `--doh-resolver`
+pub fn warn_unsupported() {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    if !SUPPORTED {
+        WARNED.call_once(|| warn!("⚠️ This build does not include DoH support (build with --features doh), using the system resolver"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert!(parse_url("https://1.1.1.1/dns-query").is_ok());
+        assert!(parse_url("https://cloudflare-dns.com/dns-query").is_ok());
+        assert!(parse_url("http://1.1.1.1/dns-query").is_err());
+        assert!(parse_url("1.1.1.1").is_err());
+    }
+
+    #[test]
+    fn test_parse_answer() {
+        let response = serde_json::json!({
+            "Status": 0,
+            "Answer": [
+                {"name": "api.cloudflare.com", "type": 5, "TTL": 300, "data": "cname.example.net."},
+                {"name": "api.cloudflare.com", "type": 1, "TTL": 300, "data": "104.19.192.29"},
+                {"name": "api.cloudflare.com", "type": 1, "TTL": 300, "data": "104.19.193.29"}
+            ]
+        });
+        let addresses = parse_answer(&response, TYPE_A).unwrap();
+        assert_eq!(addresses, vec!["104.19.192.29".parse::<IpAddr>().unwrap(), "104.19.193.29".parse().unwrap()]);
+        assert!(parse_answer(&response, TYPE_AAAA).unwrap().is_empty());
+
+        assert!(parse_answer(&serde_json::json!({"Status": 3}), TYPE_A).unwrap().is_empty());
+        assert!(parse_answer(&serde_json::json!({"Status": 2}), TYPE_A).is_err());
+    }
+}
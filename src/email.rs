@@ -0,0 +1,341 @@
+//! Summary emails over SMTP (`--smtp-server`)
+//!
+//! After a cycle that matches `--email-on` (a record changed, something
+//! failed, or every cycle) the human-readable summary is mailed from a
+//! background task with a timeout, so a slow or unreachable mail server never
+//! delays or fails the update loop. Before the process exits, [`flush`] waits
+//! for deliveries still in flight. Delivery uses `lettre` and is only built
+//! with the `email` feature; other builds accept the options and warn once.
+
+use anyhow::Result;
+use log::warn;
+use std::time::Duration;
+
+/// Whether this build can deliver mail
+pub const SUPPORTED: bool = cfg!(feature = "email");
+
+/// Longest time one delivery may take, connection and TLS handshake included
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Deliveries started by [`send`] that may still be running
+#[cfg(feature = "email")]
+static PENDING: std::sync::Mutex<Vec<tokio::task::JoinHandle<()>>> = std::sync::Mutex::new(Vec::new());
+
+/// Which cycles send an email (`--email-on change|failure|always`, comma-separated)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmailOn {
+    change: bool,
+    failure: bool,
+    always: bool,
+}
+
+impl Default for EmailOn {
+    fn default() -> Self {
+        Self { change: true, failure: true, always: false }
+    }
+}
+
+impl EmailOn {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut on = Self { change: false, failure: false, always: false };
+        for trigger in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match trigger.to_ascii_lowercase().as_str() {
+                "change" => on.change = true,
+                "failure" => on.failure = true,
+                "always" => on.always = true,
+                _ => return Err(anyhow::anyhow!("EMAIL_ON must be change, failure or always (comma-separated), got {}", trigger)),
+            }
+        }
+        if on == (Self { change: false, failure: false, always: false }) {
+            return Err(anyhow::anyhow!("EMAIL_ON must name at least one of change, failure or always"));
+        }
+        Ok(on)
+    }
+
+    /// Whether a cycle that `changed` records and/or `failed` should be mailed
+    pub fn wants(&self, changed: bool, failed: bool) -> bool {
+        self.always || (self.change && changed) || (self.failure && failed)
+    }
+}
+
+/// How the SMTP connection is secured (`--smtp-tls`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsMode {
+    /// Plain connection upgraded with STARTTLS (required), port 587 by default
+    #[default]
+    StartTls,
+    /// TLS from the first byte (SMTPS), port 465 by default
+    Tls,
+    /// Unencrypted, for a relay on the local host or network, port 25 by default
+    None,
+}
+
+impl TlsMode {
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.trim().to_ascii_lowercase().as_str() {
+            "" | "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            "none" => Ok(Self::None),
+            other => Err(anyhow::anyhow!("SMTP_TLS must be starttls, tls or none, got {}", other)),
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            Self::StartTls => 587,
+            Self::Tls => 465,
+            Self::None => 25,
+        }
+    }
+}
+
+/// Where and as whom summary emails are sent
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+pub struct SmtpSettings {
+    pub server: String,
+    pub port: Option<u16>,
+    pub tls: TlsMode,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl SmtpSettings {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(self.tls.default_port())
+    }
+}
+
+/// Check a mailbox, either `user@example.com` or `Name <user@example.com>`
+pub fn validate_mailbox(spec: &str) -> Result<()> {
+    let spec = spec.trim();
+    let address = match spec.rsplit_once('<') {
+        Some((_, rest)) => rest.strip_suffix('>').unwrap_or("<"),
+        None => spec,
+    };
+    let valid = match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && domain.contains('.') && !domain.contains('@') && !address.contains(char::is_whitespace)
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(anyhow::anyhow!("Invalid email address: {}", spec));
+    }
+    Ok(())
+}
+
+/// Subject line for a cycle summary from `host`
+pub fn subject(host: &str, changed: bool, failed: bool) -> String {
+    let what = match (changed, failed) {
+        (_, true) => "DNS update failed",
+        (true, false) => "DNS records updated",
+        (false, false) => "DNS records unchanged",
+    };
+    format!("[cloudflare-ddns] {} on {}", what, host)
+}
+
+/// Mail `body` in the background; failures are only logged
+pub fn send(settings: SmtpSettings, subject: String, body: String) {
+    #[cfg(feature = "email")]
+    {
+        let delivery = tokio::spawn(async move {
+            match tokio::time::timeout(TIMEOUT, deliver(&settings, &subject, &body)).await {
+                Ok(Ok(())) => log::debug!("Summary email sent to {}", settings.to.join(", ")),
+                Ok(Err(e)) => warn!("⚠️ Failed to send summary email via {}: {:#}", settings.server, e),
+                Err(_) => warn!("⚠️ Sending summary email via {} timed out after {}s", settings.server, TIMEOUT.as_secs()),
+            }
+        });
+        let mut pending = PENDING.lock().unwrap();
+        pending.retain(|delivery| !delivery.is_finished());
+        pending.push(delivery);
+    }
+    #[cfg(not(feature = "email"))]
+    {
+        let _ = (settings, subject, body);
+        warn_unsupported();
+    }
+}
+
+/// Wait (at most `TIMEOUT`) for emails still being sent, so exiting right after a cycle
+/// (`--once`, `--max-cycles`) does not drop its summary
+pub async fn flush() {
+    #[cfg(feature = "email")]
+    {
+        let pending = std::mem::take(&mut *PENDING.lock().unwrap());
+        if pending.is_empty() {
+            return;
+        }
+        log::debug!("Waiting for {} summary email(s) to be sent", pending.len());
+        if tokio::time::timeout(TIMEOUT, futures::future::join_all(pending)).await.is_err() {
+            warn!("⚠️ Summary email still not sent after {}s, exiting without it", TIMEOUT.as_secs());
+        }
+    }
+}
+
+/// Warn (once) that this build cannot send mail
+pub fn warn_unsupported() {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    if !SUPPORTED {
+        WARNED.call_once(|| warn!("⚠️ This build does not include email support (build with --features email), summary emails disabled"));
+    }
+}
+
+#[cfg(feature = "email")]
+async fn deliver(settings: &SmtpSettings, subject: &str, body: &str) -> Result<()> {
+    use lettre::message::header::ContentType;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+    let mut message = Message::builder()
+        .from(settings.from.trim().parse()?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in &settings.to {
+        message = message.to(to.trim().parse()?);
+    }
+    let message = message.body(body.to_string())?;
+
+    let transport = match settings.tls {
+        TlsMode::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.server)?,
+        TlsMode::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.server)?,
+        TlsMode::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.server),
+    };
+    let mut transport = transport.port(settings.port()).timeout(Some(TIMEOUT));
+    if let (Some(user), Some(password)) = (&settings.user, &settings.password) {
+        transport = transport.credentials(Credentials::new(user.clone(), password.clone()));
+    }
+    transport.build().send(message).await?;
+    Ok(())
+}
+
+/// A minimal SMTP server on a free local port; the returned buffer receives each
+/// message's data before the server acknowledges it
+#[cfg(all(test, feature = "email"))]
+pub async fn mock_smtp_server() -> (u16, std::sync::Arc<std::sync::Mutex<String>>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+    let data = received.clone();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+        let mut in_data = false;
+        while let Some(line) = lines.next_line().await.unwrap() {
+            if in_data {
+                if line == "." {
+                    in_data = false;
+                    write.write_all(b"250 OK\r\n").await.unwrap();
+                } else {
+                    let mut data = data.lock().unwrap();
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                continue;
+            }
+            let reply: &[u8] = match line.split_whitespace().next().unwrap_or_default().to_ascii_uppercase().as_str() {
+                "EHLO" | "HELO" => b"250 localhost\r\n",
+                "DATA" => {
+                    in_data = true;
+                    b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                }
+                "QUIT" => {
+                    write.write_all(b"221 Bye\r\n").await.unwrap();
+                    break;
+                }
+                _ => b"250 OK\r\n",
+            };
+            write.write_all(reply).await.unwrap();
+        }
+    });
+    (port, received)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_on() {
+        let default = EmailOn::default();
+        assert!(default.wants(true, false) && default.wants(false, true));
+        assert!(!default.wants(false, false));
+
+        let failure = EmailOn::parse("failure").unwrap();
+        assert!(failure.wants(true, true));
+        assert!(!failure.wants(true, false));
+        assert!(EmailOn::parse("always").unwrap().wants(false, false));
+        assert_eq!(EmailOn::parse(" Change , failure ").unwrap(), default);
+
+        assert!(EmailOn::parse("sometimes").is_err());
+        assert!(EmailOn::parse(" , ").is_err());
+    }
+
+    #[test]
+    fn test_tls_mode() {
+        assert_eq!(TlsMode::parse("").unwrap(), TlsMode::StartTls);
+        assert_eq!(TlsMode::parse("TLS").unwrap(), TlsMode::Tls);
+        assert!(TlsMode::parse("ssl").is_err());
+
+        let settings = SmtpSettings {
+            server: "smtp.example.com".to_string(),
+            port: None,
+            tls: TlsMode::StartTls,
+            user: None,
+            password: None,
+            from: "ddns@example.com".to_string(),
+            to: vec!["admin@example.com".to_string()],
+        };
+        assert_eq!(settings.port(), 587);
+        assert_eq!(SmtpSettings { tls: TlsMode::Tls, ..settings.clone() }.port(), 465);
+        assert_eq!(SmtpSettings { port: Some(2525), ..settings }.port(), 2525);
+    }
+
+    #[test]
+    fn test_validate_mailbox() {
+        assert!(validate_mailbox("admin@example.com").is_ok());
+        assert!(validate_mailbox("DDNS Bot <ddns@example.com>").is_ok());
+        assert!(validate_mailbox("admin").is_err());
+        assert!(validate_mailbox("admin@localhost").is_err());
+        assert!(validate_mailbox("Bot <ddns@example.com").is_err());
+        assert!(validate_mailbox("ad min@example.com").is_err());
+    }
+
+    #[test]
+    fn test_subject() {
+        assert_eq!(subject("nas", true, false), "[cloudflare-ddns] DNS records updated on nas");
+        // 有失败时以失败为主
+        assert_eq!(subject("nas", true, true), "[cloudflare-ddns] DNS update failed on nas");
+        assert_eq!(subject("nas", false, false), "[cloudflare-ddns] DNS records unchanged on nas");
+    }
+
+    #[cfg(feature = "email")]
+    #[tokio::test]
+    async fn test_deliver() {
+        // 最小的 SMTP 服务端，记录收到的邮件内容
+        let (port, received) = mock_smtp_server().await;
+        let settings = SmtpSettings {
+            server: "127.0.0.1".to_string(),
+            port: Some(port),
+            tls: TlsMode::None,
+            user: None,
+            password: None,
+            from: "DDNS <ddns@example.com>".to_string(),
+            to: vec!["admin@example.com".to_string()],
+        };
+        deliver(&settings, "[cloudflare-ddns] DNS records updated on nas", "~ home.example.com A: 192.0.2.1 -> 192.0.2.2\n")
+            .await
+            .unwrap();
+        let data = received.lock().unwrap().clone();
+        assert!(data.contains("Subject: [cloudflare-ddns] DNS records updated on nas"));
+        assert!(data.contains("To: admin@example.com"));
+        assert!(data.contains("~ home.example.com A: 192.0.2.1 -> 192.0.2.2"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Domain name glob patterns (`--domain-filter`)
+//!
+//! `*` matches any run of characters, dots included, so `*.a.*` spans several
+//! labels; `?` matches exactly one character. Matching ignores ASCII case, like
+//! DNS names do.
+
+use anyhow::Result;
+
+/// Check that `pattern` only contains hostname characters and wildcards
+pub fn validate(pattern: &str) -> Result<()> {
+    if let Some(c) = pattern.chars().find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '*' | '?'))) {
+        return Err(anyhow::anyhow!("Invalid character {:?} in domain filter {}", c, pattern));
+    }
+    Ok(())
+}
+
+/// Whether `name` matches `pattern`
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase().into_bytes();
+    let name = name.to_ascii_lowercase().into_bytes();
+    let (mut p, mut n) = (0, 0);
+    // 最近一个 * 的位置及其当前匹配到的名称位置，失配时回溯让 * 多吞一个字符
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*.home.example.com", "nas.home.example.com"));
+        assert!(matches("*.home.example.com", "a.b.home.example.com"));
+        assert!(!matches("*.home.example.com", "home.example.com"));
+        assert!(matches("*.a.*", "x.a.example.com"));
+        assert!(!matches("*.a.*", "x.b.example.com"));
+        assert!(matches("node-?.example.com", "NODE-1.example.com"));
+        assert!(!matches("node-?.example.com", "node-10.example.com"));
+        assert!(matches("*", "example.com"));
+        assert!(matches("example.com", "example.com"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("*.home.example.com").is_ok());
+        assert!(validate("node-?.example.com").is_ok());
+        assert!(validate("[ab].example.com").is_err());
+        assert!(validate("*.example.com,*.example.net").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Health check endpoint (`--health-addr`)
+//!
+//! A minimal HTTP/1.1 server answering `GET /healthz` for liveness and
+//! readiness probes: 200 while the last cycle without failures finished less
+//! than `--health-max-age` seconds ago, 503 before the first such cycle and
+//! once it is older. It only reads the outcome recorded after each cycle, so
+//! it is independent of the StatsD and report file outputs.
+
+use anyhow::Result;
+use log::{debug, info};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Path of the health endpoint
+const PATH: &str = "/healthz";
+
+/// Longest request head read before answering
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// When the last cycle without failures finished
+static LAST_SUCCESS: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Record the outcome of a finished update cycle
+pub fn record_cycle(ok: bool) {
+    if ok {
+        *LAST_SUCCESS.lock().unwrap() = Some(Instant::now());
+    }
+}
+
+/// Check a listen address (`ip:port`) without binding it
+pub fn validate_addr(spec: &str) -> Result<()> {
+    spec.parse::<SocketAddr>()
+        .map(|_| ())
+        .map_err(|_| anyhow::anyhow!("Health address must be ip:port (e.g. 127.0.0.1:8080), got {}", spec))
+}
+
+/// Bind `addr` and answer health probes in the background
+pub async fn serve(addr: &str, max_age: Duration) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Unable to listen on {}: {}", addr, e))?;
+    let local = listener.local_addr()?;
+    info!("🩺 Health endpoint listening on http://{}{}", local, PATH);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = handle(stream, max_age).await {
+                            debug!("Health request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => debug!("Failed to accept health connection: {}", e),
+            }
+        }
+    });
+    Ok(local)
+}
+
+async fn handle(mut stream: TcpStream, max_age: Duration) -> Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    // 只需读到请求头结束
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let n = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut buf)).await??;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let age = LAST_SUCCESS.lock().unwrap().map(|at| at.elapsed());
+    let response = respond(request.lines().next().unwrap_or_default(), age, max_age);
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// The full HTTP response to `request_line`, given the age of the last successful cycle
+fn respond(request_line: &str, last_success_age: Option<Duration>, max_age: Duration) -> String {
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let path = target.split('?').next().unwrap_or_default();
+    let (status, body) = if path != PATH {
+        ("404 Not Found", "{\"error\":\"not found\"}".to_string())
+    } else if method != "GET" && method != "HEAD" {
+        ("405 Method Not Allowed", "{\"error\":\"method not allowed\"}".to_string())
+    } else {
+        let (status, state) = match last_success_age {
+            None => ("503 Service Unavailable", "starting"),
+            Some(age) if age > max_age => ("503 Service Unavailable", "stale"),
+            Some(_) => ("200 OK", "ok"),
+        };
+        let body = serde_json::json!({
+            "status": state,
+            "last_success_age": last_success_age.map(|age| age.as_secs()),
+            "max_age": max_age.as_secs(),
+        });
+        (status, body.to_string())
+    };
+    let content = if method == "HEAD" { "" } else { body.as_str() };
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_AGE: Duration = Duration::from_secs(900);
+
+    #[test]
+    fn test_respond() {
+        let ok = respond("GET /healthz HTTP/1.1", Some(Duration::from_secs(12)), MAX_AGE);
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.ends_with("\r\n\r\n{\"last_success_age\":12,\"max_age\":900,\"status\":\"ok\"}"));
+
+        // 尚无成功周期或已过期
+        let starting = respond("GET /healthz HTTP/1.1", None, MAX_AGE);
+        assert!(starting.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(starting.contains("\"status\":\"starting\""));
+        let stale = respond("GET /healthz?verbose HTTP/1.1", Some(Duration::from_secs(901)), MAX_AGE);
+        assert!(stale.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(stale.contains("\"status\":\"stale\""));
+
+        // HEAD 只返回头部
+        let head = respond("HEAD /healthz HTTP/1.1", Some(Duration::ZERO), MAX_AGE);
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n") && head.ends_with("\r\n\r\n"));
+
+        assert!(respond("GET / HTTP/1.1", None, MAX_AGE).starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(respond("POST /healthz HTTP/1.1", None, MAX_AGE).starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+        assert!(respond("", None, MAX_AGE).starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_validate_addr() {
+        assert!(validate_addr("127.0.0.1:8080").is_ok());
+        assert!(validate_addr("[::]:8080").is_ok());
+        assert!(validate_addr("localhost:8080").is_err());
+        assert!(validate_addr("127.0.0.1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        let addr = serve("127.0.0.1:0", MAX_AGE).await.unwrap();
+        let get = |path: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        assert!(get("/healthz").await.contains("\r\nContent-Type: application/json\r\n"));
+        assert!(get("/metrics").await.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        record_cycle(true);
+        assert!(get("/healthz").await.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Local network interface addresses
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+/// Get a usable local address of `interface` for the given family.
+///
+/// Loopback and link-local addresses are skipped; the result is suitable as
+/// the source address for outbound requests through that interface.
+pub fn local_address(interface: &str, ipv6: bool) -> Result<IpAddr> {
+    if_addrs::get_if_addrs()?
+        .into_iter()
+        .filter(|iface| iface.name == interface)
+        .map(|iface| iface.ip())
+        .find(|ip| ip.is_ipv6() == ipv6 && is_usable_address(ip))
+        .ok_or_else(|| anyhow::anyhow!(
+            "No usable {} address found on interface {}",
+            if ipv6 { "IPv6" } else { "IPv4" },
+            interface
+        ))
+}
+
+/// Whether `ip` is a usable (non-loopback, non-link-local) address
+pub fn is_usable_address(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => !v4.is_loopback() && !v4.is_link_local() && !v4.is_unspecified(),
+        IpAddr::V6(v6) => !v6.is_loopback() && (v6.segments()[0] & 0xffc0) != 0xfe80 && !v6.is_unspecified(),
+    }
+}
+
+/// All usable addresses currently assigned to `interface`
+pub fn usable_addresses(interface: &str) -> Result<BTreeSet<IpAddr>> {
+    Ok(if_addrs::get_if_addrs()?
+        .into_iter()
+        .filter(|iface| iface.name == interface)
+        .map(|iface| iface.ip())
+        .filter(is_usable_address)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_usable_address() {
+        assert!(is_usable_address(&"192.168.1.10".parse().unwrap()));
+        assert!(is_usable_address(&"2001:db8::1".parse().unwrap()));
+        assert!(!is_usable_address(&"127.0.0.1".parse().unwrap()));
+        assert!(!is_usable_address(&"169.254.1.1".parse().unwrap()));
+        assert!(!is_usable_address(&"fe80::1".parse().unwrap()));
+        assert!(!is_usable_address(&"::1".parse().unwrap()));
+    }
+}
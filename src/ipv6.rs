@@ -0,0 +1,126 @@
+//! IPv6 helpers for prefix-delegated networks
+//!
+//! With prefix delegation only the network prefix is stable while the host
+//! bits may rotate, so the published AAAA can be built from the current
+//! prefix plus a fixed host suffix.
+
+use anyhow::Result;
+use std::net::{IpAddr, Ipv6Addr};
+
+/// Whether `addr` is a global unicast address (2000::/3)
+pub fn is_global_unicast(addr: &Ipv6Addr) -> bool {
+    addr.segments()[0] & 0xe000 == 0x2000
+}
+
+/// Drop a `%scope` zone suffix from a textual IPv6 address (`fe80::1%eth0` -> `fe80::1`).
+/// Cloudflare rejects scoped addresses, and they never equal a record's content
+pub fn strip_scope(addr: &str) -> &str {
+    match addr.split_once('%') {
+        Some((ip, _)) if ip.contains(':') => ip,
+        _ => addr,
+    }
+}
+
+/// Whether two textual addresses are the same IP, ignoring IPv6 scope and notation
+/// (`2001:db8::1` equals `2001:DB8:0::1%eth0`); non-addresses are compared as text
+pub fn same_address(a: &str, b: &str) -> bool {
+    match (strip_scope(a).parse::<IpAddr>(), strip_scope(b).parse::<IpAddr>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Parse a host suffix such as `::1234` and check it fits below `prefix_len`
+pub fn parse_suffix(suffix: &str, prefix_len: u8) -> Result<Ipv6Addr> {
+    if prefix_len == 0 || prefix_len >= 128 {
+        return Err(anyhow::anyhow!("IPv6 prefix length must be between 1 and 127, got {}", prefix_len));
+    }
+    let parsed: Ipv6Addr = suffix
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid IPv6 suffix: {}", suffix))?;
+
+    let prefix_mask = u128::MAX << (128 - u32::from(prefix_len));
+    if u128::from(parsed) & prefix_mask != 0 {
+        return Err(anyhow::anyhow!(
+            "IPv6 suffix {} does not fit in the {} host bits of a /{} prefix",
+            suffix, 128 - u32::from(prefix_len), prefix_len
+        ));
+    }
+    Ok(parsed)
+}
+
+/// Combine the first `prefix_len` bits of `prefix_source` with `suffix`
+pub fn combine_prefix_suffix(prefix_source: Ipv6Addr, prefix_len: u8, suffix: Ipv6Addr) -> Ipv6Addr {
+    let prefix_mask = u128::MAX << (128 - u32::from(prefix_len));
+    Ipv6Addr::from((u128::from(prefix_source) & prefix_mask) | (u128::from(suffix) & !prefix_mask))
+}
+
+/// Get the first global IPv6 address assigned to `interface`
+pub fn interface_global_ipv6(interface: &str) -> Result<Ipv6Addr> {
+    if_addrs::get_if_addrs()?
+        .into_iter()
+        .filter(|iface| iface.name == interface)
+        .find_map(|iface| match iface.ip() {
+            IpAddr::V6(addr) if is_global_unicast(&addr) => Some(addr),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow::anyhow!("No global IPv6 address found on interface {}", interface))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_prefix_suffix() {
+        let detected: Ipv6Addr = "2001:db8:1:2:aaaa:bbbb:cccc:dddd".parse().unwrap();
+
+        let suffix = parse_suffix("::1234", 64).unwrap();
+        assert_eq!(
+            combine_prefix_suffix(detected, 64, suffix),
+            "2001:db8:1:2::1234".parse::<Ipv6Addr>().unwrap()
+        );
+
+        // /56: 子网号属于后缀部分
+        let suffix = parse_suffix("0:0:0:ff::1", 56).unwrap();
+        assert_eq!(
+            combine_prefix_suffix(detected, 56, suffix),
+            "2001:db8:1:ff::1".parse::<Ipv6Addr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_strip_scope() {
+        assert_eq!(strip_scope("fe80::1%eth0"), "fe80::1");
+        assert_eq!(strip_scope("2001:db8::1%2"), "2001:db8::1");
+        assert_eq!(strip_scope("2001:db8::1"), "2001:db8::1");
+        assert_eq!(strip_scope("192.0.2.1"), "192.0.2.1");
+        // 非地址文本保持原样
+        assert_eq!(strip_scope("100%"), "100%");
+
+        assert!(same_address("2001:db8::1", "2001:db8::1%eth0"));
+        assert!(same_address("2001:DB8:0::1%eth0", "2001:db8::1"));
+        assert!(!same_address("2001:db8::1", "2001:db8::2%eth0"));
+        assert!(same_address("192.0.2.1", "192.0.2.1"));
+        assert!(!same_address("not-an-ip", "not-an-ip%eth0"));
+    }
+
+    #[test]
+    fn test_parse_suffix_validation() {
+        // 后缀超出主机位
+        assert!(parse_suffix("1::1234", 64).is_err());
+        assert!(parse_suffix("0:0:0:100::1", 56).is_err());
+        // 非法格式与前缀长度
+        assert!(parse_suffix("not-an-ip", 64).is_err());
+        assert!(parse_suffix("::1", 0).is_err());
+        assert!(parse_suffix("::1", 128).is_err());
+    }
+
+    #[test]
+    fn test_is_global_unicast() {
+        assert!(is_global_unicast(&"2001:db8::1".parse().unwrap()));
+        assert!(!is_global_unicast(&"fe80::1".parse().unwrap()));
+        assert!(!is_global_unicast(&"fd00::1".parse().unwrap()));
+        assert!(!is_global_unicast(&"::1".parse().unwrap()));
+    }
+}
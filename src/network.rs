@@ -0,0 +1,122 @@
+//! Current network detection (`--require-network`)
+//!
+//! The network is identified by its default IPv4 gateway: the identifier is
+//! `gw-` followed by the first 12 hex digits of the SHA-256 of the gateway
+//! address. This works without extra privileges on every platform that can
+//! report its routing table, unlike SSIDs or gateway MAC addresses. On Linux
+//! the gateway is read from `/proc/net/route`; macOS and the BSDs ask
+//! `route -n get default`.
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// The network the host is currently attached to
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrentNetwork {
+    pub gateway: IpAddr,
+    pub id: String,
+}
+
+impl CurrentNetwork {
+    pub fn from_gateway(gateway: IpAddr) -> Self {
+        let digest = Sha256::digest(gateway.to_string().as_bytes());
+        Self { gateway, id: format!("gw-{}", &hex::encode(digest)[..12]) }
+    }
+
+    /// Whether `required` names this network, by identifier or by gateway address
+    pub fn matches(&self, required: &str) -> bool {
+        let required = required.trim();
+        required.eq_ignore_ascii_case(&self.id) || required.parse::<IpAddr>().is_ok_and(|ip| ip == self.gateway)
+    }
+}
+
+impl std::fmt::Display for CurrentNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (gateway {})", self.id, self.gateway)
+    }
+}
+
+/// Detect the current network from the default gateway
+pub fn detect() -> Result<CurrentNetwork> {
+    Ok(CurrentNetwork::from_gateway(default_gateway()?))
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Result<IpAddr> {
+    let table = std::fs::read_to_string("/proc/net/route")?;
+    parse_proc_route(&table)
+        .map(IpAddr::V4)
+        .ok_or_else(|| anyhow::anyhow!("No default gateway in /proc/net/route"))
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+fn default_gateway() -> Result<IpAddr> {
+    let output = std::process::Command::new("route").args(["-n", "get", "default"]).output()?;
+    parse_route_get(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| anyhow::anyhow!("No default gateway reported by route -n get default"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd")))]
+fn default_gateway() -> Result<IpAddr> {
+    Err(anyhow::anyhow!("Network detection is not supported on this platform"))
+}
+
+/// Gateway of the default route in `/proc/net/route` (columns are little-endian hex)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_route(table: &str) -> Option<Ipv4Addr> {
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, "00000000", gateway, ..] => {
+                let gateway = u32::from_str_radix(gateway, 16).ok()?;
+                (gateway != 0).then(|| Ipv4Addr::from(gateway.to_le_bytes()))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// The `gateway:` line of `route -n get default`
+#[cfg_attr(not(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd")), allow(dead_code))]
+fn parse_route_get(output: &str) -> Option<IpAddr> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("gateway:"))
+        .and_then(|gateway| gateway.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_route() {
+        let table = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                     eth0\t0000A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n\
+                     eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n";
+        assert_eq!(parse_proc_route(table), Some(Ipv4Addr::new(192, 168, 1, 1)));
+        assert_eq!(parse_proc_route("Iface\tDestination\tGateway\n"), None);
+    }
+
+    #[test]
+    fn test_parse_route_get() {
+        let output = "   route to: default\ndestination: default\n       mask: default\n    gateway: 192.168.1.1\n  interface: en0\n";
+        assert_eq!(parse_route_get(output), Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))));
+        assert_eq!(parse_route_get("route: writing to routing socket: not in table\n"), None);
+    }
+
+    #[test]
+    fn test_matches() {
+        let home = CurrentNetwork::from_gateway("192.168.1.1".parse().unwrap());
+        assert!(home.id.starts_with("gw-") && home.id.len() == 15);
+        assert!(home.matches(&home.id));
+        assert!(home.matches(&home.id.to_uppercase()));
+        assert!(home.matches("192.168.1.1"));
+        // 咖啡店网络的网关不同
+        let cafe = CurrentNetwork::from_gateway("10.0.0.1".parse().unwrap());
+        assert_ne!(cafe.id, home.id);
+        assert!(!cafe.matches(&home.id));
+        assert!(!cafe.matches("192.168.1.1"));
+    }
+}
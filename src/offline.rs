@@ -0,0 +1,249 @@
+//! Offline simulation (`--offline`)
+//!
+//! Stands in for both the public IP services and the Cloudflare API so the
+//! whole update flow can run without credentials or network access. Records
+//! live in memory, optionally seeded from `--offline-records`, and every write
+//! is logged as what would have been sent.
+
+use anyhow::Result;
+use log::info;
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Zone ID reported for lookups by zone name
+const OFFLINE_ZONE_ID: &str = "offline-zone";
+
+/// Addresses reported when `--offline-ip` does not cover a family (documentation ranges)
+const DEFAULT_IPV4: &str = "203.0.113.10";
+const DEFAULT_IPV6: &str = "2001:db8::10";
+
+/// Simulated IP services and Cloudflare API
+#[derive(Debug)]
+pub struct OfflineCloudflare {
+    ipv4: String,
+    ipv6: String,
+    records: Mutex<Vec<Value>>,
+}
+
+impl OfflineCloudflare {
+    /// `ip` is returned as the public address of its family; `records` is a JSON array
+    /// of Cloudflare-style records (`name`, `type`, `content`, optional `id`)
+    pub fn new(ip: Option<&str>, records: Vec<Value>) -> Result<Self> {
+        let (mut ipv4, mut ipv6) = (DEFAULT_IPV4.to_string(), DEFAULT_IPV6.to_string());
+        match ip.map(|ip| ip.parse::<std::net::IpAddr>()) {
+            Some(Ok(std::net::IpAddr::V4(v4))) => ipv4 = v4.to_string(),
+            Some(Ok(std::net::IpAddr::V6(v6))) => ipv6 = v6.to_string(),
+            Some(Err(_)) => return Err(anyhow::anyhow!("--offline-ip must be an IP address, got {}", ip.unwrap_or_default())),
+            None => {}
+        }
+
+        let mut seeded = Vec::new();
+        for (index, mut record) in records.into_iter().enumerate() {
+            if record["name"].as_str().is_none() || record["type"].as_str().is_none() {
+                return Err(anyhow::anyhow!("Offline record {} needs \"name\" and \"type\"", index + 1));
+            }
+            if record["id"].as_str().is_none() {
+                record["id"] = json!(format!("offline-{}", index + 1));
+            }
+            seeded.push(record);
+        }
+        Ok(Self { ipv4, ipv6, records: Mutex::new(seeded) })
+    }
+
+    /// Load canned records from a JSON file
+    pub fn load(ip: Option<&str>, records_path: Option<&Path>) -> Result<Self> {
+        let records = match records_path {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow::anyhow!("Unable to read offline records {}: {}", path.display(), e))?;
+                serde_json::from_str(&text)
+                    .map_err(|e| anyhow::anyhow!("Invalid offline records {}: {}", path.display(), e))?
+            }
+            None => Vec::new(),
+        };
+        Self::new(ip, records)
+    }
+
+    /// The simulated public IP for A or AAAA detection
+    pub fn public_ip(&self, record_type: &str) -> String {
+        if record_type == "AAAA" { self.ipv6.clone() } else { self.ipv4.clone() }
+    }
+
+    /// Answer a Cloudflare API request like the real endpoint would
+    pub fn respond(&self, method: &str, url: &Url, body: Option<&[u8]>) -> Value {
+        let body: Value = body.and_then(|b| serde_json::from_slice(b).ok()).unwrap_or(Value::Null);
+        let query = |key: &str| url.query_pairs().find(|(k, _)| k == key).map(|(_, v)| v.into_owned());
+        let path: Vec<&str> = url
+            .path()
+            .trim_start_matches("/client/v4/")
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        match (method, path.as_slice()) {
+            ("GET", ["user", "tokens", "verify"]) => success(json!({ "id": "offline-token", "status": "active" })),
+            ("GET", ["zones"]) => {
+                let name = query("name").unwrap_or_else(|| self.zone_name());
+                success(json!([{ "id": OFFLINE_ZONE_ID, "name": name }]))
+            }
+            ("GET", ["zones", zone_id]) => success(json!({ "id": zone_id, "name": self.zone_name() })),
+            ("GET", ["zones", _, "dns_records"]) => {
+                let (name, record_type) = (query("name"), query("type"));
+                let records: Vec<Value> = self
+                    .records
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|r| name.as_deref().is_none_or(|n| r["name"] == n))
+                    .filter(|r| record_type.as_deref().is_none_or(|t| r["type"] == t))
+                    .cloned()
+                    .collect();
+                let total = records.len();
+                json!({ "success": true, "errors": [], "result": records, "result_info": { "total_count": total } })
+            }
+            ("POST", ["zones", _, "dns_records", "batch"]) => {
+                let mut patches = Vec::new();
+                for r in body["patches"].as_array().into_iter().flatten() {
+                    let id = r["id"].as_str().unwrap_or_default();
+                    match self.patch(id, r) {
+                        Some(record) => patches.push(record),
+                        None => return not_found(id),
+                    }
+                }
+                let posts: Vec<Value> = body["posts"].as_array().into_iter().flatten().map(|r| self.create(r)).collect();
+                success(json!({ "patches": patches, "posts": posts }))
+            }
+            ("POST", ["zones", _, "dns_records"]) => success(self.create(&body)),
+            ("PATCH", ["zones", _, "dns_records", id]) => match self.patch(id, &body) {
+                Some(record) => success(record),
+                None => not_found(id),
+            },
+            ("PUT", ["zones", _, "dns_records", id]) => {
+                let mut record = body.clone();
+                record["id"] = json!(id);
+                success(self.put(&record))
+            }
+            ("DELETE", ["zones", _, "dns_records", id]) => {
+                let removed = {
+                    let mut records = self.records.lock().unwrap();
+                    let before = records.len();
+                    records.retain(|r| r["id"] != *id);
+                    before != records.len()
+                };
+                if !removed {
+                    return not_found(id);
+                }
+                info!("🧪 Offline: would DELETE record {}", id);
+                success(json!({ "id": id }))
+            }
+            _ => json!({
+                "success": false,
+                "errors": [{ "code": 0, "message": format!("offline mode does not simulate {} {}", method, url.path()) }],
+            }),
+        }
+    }
+
+    /// Zone name guessed from the canned records (`example.com` when there are none)
+    fn zone_name(&self) -> String {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .find_map(|r| crate::zone::registrable_domain(r["name"].as_str()?).ok())
+            .unwrap_or_else(|| "example.com".to_string())
+    }
+
+    fn create(&self, body: &Value) -> Value {
+        let mut records = self.records.lock().unwrap();
+        let mut record = with_content(body);
+        record["id"] = json!(format!("offline-{}", records.len() + 1));
+        info!("🧪 Offline: would POST {} {} -> {}", record["type"], record["name"], record["content"]);
+        records.push(record.clone());
+        record
+    }
+
+    fn put(&self, body: &Value) -> Value {
+        let mut records = self.records.lock().unwrap();
+        let record = with_content(body);
+        info!("🧪 Offline: would PUT {} {} -> {}", record["type"], record["name"], record["content"]);
+        match records.iter_mut().find(|r| r["id"] == record["id"]) {
+            Some(existing) => *existing = record.clone(),
+            None => records.push(record.clone()),
+        }
+        record
+    }
+
+    /// Change only the fields in `body`, keeping the others (comment, tags)
+    fn patch(&self, id: &str, body: &Value) -> Option<Value> {
+        let mut records = self.records.lock().unwrap();
+        let existing = records.iter_mut().find(|r| r["id"] == id)?;
+        for (key, value) in body.as_object().into_iter().flatten() {
+            existing[key] = value.clone();
+        }
+        *existing = with_content(existing);
+        info!("🧪 Offline: would PATCH {} {} -> {}", existing["type"], existing["name"], existing["content"]);
+        Some(existing.clone())
+    }
+}
+
+/// Records sent with structured `data` (CAA) are reported back with presentation `content`
+fn with_content(body: &Value) -> Value {
+    let mut record = body.clone();
+    let data = &body["data"];
+    if body["type"] == "CAA" && data.is_object() {
+        record["content"] = json!(format!(
+            "{} {} \"{}\"",
+            data["flags"].as_u64().unwrap_or(0),
+            data["tag"].as_str().unwrap_or_default(),
+            data["value"].as_str().unwrap_or_default()
+        ));
+    }
+    record
+}
+
+fn success(result: Value) -> Value {
+    json!({ "success": true, "errors": [], "result": result })
+}
+
+fn not_found(id: &str) -> Value {
+    json!({ "success": false, "errors": [{ "code": 81044, "message": format!("Record {} does not exist.", id) }] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(path: &str) -> Url {
+        Url::parse(&format!("https://api.cloudflare.com/client/v4/{}", path)).unwrap()
+    }
+
+    #[test]
+    fn test_offline_records() {
+        let records = vec![json!({ "name": "home.example.com", "type": "A", "content": "198.51.100.1" })];
+        let offline = OfflineCloudflare::new(Some("192.0.2.1"), records).unwrap();
+        assert_eq!(offline.public_ip("A"), "192.0.2.1");
+        assert_eq!(offline.public_ip("AAAA"), DEFAULT_IPV6);
+
+        let found = offline.respond("GET", &api("zones/z/dns_records?name=home.example.com&type=A"), None);
+        assert_eq!(found["result_info"]["total_count"], 1);
+        let id = found["result"][0]["id"].as_str().unwrap().to_string();
+
+        let body = json!({ "name": "home.example.com", "type": "A", "content": "192.0.2.1" }).to_string();
+        let updated = offline.respond("PUT", &api(&format!("zones/z/dns_records/{}", id)), Some(body.as_bytes()));
+        assert_eq!(updated["success"], true);
+        let found = offline.respond("GET", &api("zones/z/dns_records?name=home.example.com&type=A"), None);
+        assert_eq!(found["result"][0]["content"], "192.0.2.1");
+
+        let body = json!({ "name": "vpn.example.com", "type": "AAAA", "content": DEFAULT_IPV6 }).to_string();
+        offline.respond("POST", &api("zones/z/dns_records"), Some(body.as_bytes()));
+        let found = offline.respond("GET", &api("zones/z/dns_records?name=vpn.example.com&type=AAAA"), None);
+        assert_eq!(found["result_info"]["total_count"], 1);
+
+        assert_eq!(offline.respond("GET", &api("zones/z"), None)["result"]["name"], "example.com");
+        assert_eq!(offline.respond("DELETE", &api("zones/z/dns_records/missing"), None)["errors"][0]["code"], 81044);
+        assert!(OfflineCloudflare::new(Some("not-an-ip"), Vec::new()).is_err());
+        assert!(OfflineCloudflare::new(None, vec![json!({ "name": "a.example.com" })]).is_err());
+    }
+}
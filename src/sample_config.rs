@@ -0,0 +1,146 @@
+//! Sample configuration file (`--init-config`)
+//!
+//! The sample is generated from the default configuration and the help text
+//! of the matching command-line options, so every key is listed and explained
+//! without maintaining the file by hand. Keys without a default are commented
+//! out, so the file loads with `--config` as written and only the required
+//! keys need filling in.
+
+use clap::Command;
+use serde_json::Value;
+
+/// Keys every setup fills in, listed first and left uncommented
+const REQUIRED: [&str; 3] = ["cf_api_token", "cf_zone_id", "dns_record_name"];
+
+/// Keys that describe where the configuration came from rather than configure anything
+const SKIPPED: [&str; 2] = ["config_source", "profile"];
+
+/// Explanations for keys that have no option of the same name
+const NOTES: [(&str, &str); 2] = [
+    ("disabled_domains", "Configured domains to skip every cycle without removing them (comma-separated)"),
+    ("platform_identifier", "Host identifier used in logs and notifications [default: the host's machine ID or name]"),
+];
+
+/// Structured sections, shown as commented examples after the plain keys
+const SECTIONS: &str = "\
+# Several zones or accounts updated from one IP detection; a target without
+# cf_api_token uses the top-level one
+# [[targets]]
+# cf_zone_id = \"\"
+# dns_record_name = \"\"
+
+# Domains checked on their own interval (cannot be combined with targets)
+# [[groups]]
+# name = \"fast\"
+# dns_record_name = \"\"
+# update_interval = 60
+
+# Named profiles selected with --profile <name>; missing keys fall back to the top level
+# [profiles.home]
+# dns_record_name = \"\"
+";
+
+/// The sample TOML for `defaults` (the serialized default configuration), with
+/// each key explained by the help of its option in `command`
+pub fn generate(defaults: &Value, command: &Command) -> String {
+    let mut sample = format!(
+        "# cloudflare-ddns {} configuration\n\
+         # Fill in the required keys, then run: cloudflare-ddns --config <this file>\n\
+         # Commented-out keys are unset. Each key can also be set as an environment\n\
+         # variable (its name in upper case), which takes precedence over this file.\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    let entries: Vec<(&String, &Value)> = defaults
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| !SKIPPED.contains(&key.as_str()))
+        .collect();
+
+    sample.push_str("\n# --- Required ---\n");
+    for key in REQUIRED {
+        if let Some((key, value)) = entries.iter().find(|(k, _)| k.as_str() == key) {
+            push_key(&mut sample, command, key, value, true);
+        }
+    }
+    sample.push_str("\n# --- Optional ---\n");
+    for (key, value) in entries.iter().filter(|(key, _)| !REQUIRED.contains(&key.as_str())) {
+        // 多目标、分组等结构化配置在末尾以示例给出
+        if !matches!(value, Value::Array(_) | Value::Object(_)) || value.as_array().is_some_and(|items| !items.is_empty()) {
+            push_key(&mut sample, command, key, value, false);
+        }
+    }
+    sample.push('\n');
+    sample.push_str(SECTIONS);
+    sample
+}
+
+/// Append `key` with its help comment; unset and empty values are commented out
+/// unless the key is `required`
+fn push_key(sample: &mut String, command: &Command, key: &str, value: &Value, required: bool) {
+    let long = key.replace('_', "-");
+    let help = command
+        .get_arguments()
+        .find(|arg| arg.get_id() == key || arg.get_long() == Some(long.as_str()))
+        .and_then(|arg| arg.get_help())
+        .map(|help| help.to_string())
+        .or_else(|| NOTES.iter().find(|(k, _)| *k == key).map(|(_, note)| note.to_string()));
+    sample.push('\n');
+    if let Some(help) = help {
+        sample.push_str(&format!("# {}\n", help.lines().next().unwrap_or_default()));
+    }
+    let line = match value {
+        Value::Null => format!("# {} =", key),
+        Value::String(text) if text.is_empty() && !required => format!("# {} = \"\"", key),
+        // JSON 的字符串、数字、布尔值与数组写法在 TOML 中同样有效
+        other => format!("{} = {}", key, other),
+    };
+    sample.push_str(&line);
+    sample.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    fn command() -> Command {
+        Command::new("cloudflare-ddns")
+            .arg(Arg::new("cf_api_token").long("cf-api-token").help("Cloudflare API token"))
+            .arg(Arg::new("ttl").long("ttl").help("DNS record TTL\nin seconds"))
+            .arg(Arg::new("update_interval").long("update-interval").help("Update interval"))
+            .arg(Arg::new("fallback_record_type").long("fallback-type").help("Fallback record type"))
+            .arg(Arg::new("webhook_url").long("webhook-url").help("Webhook notified on changes"))
+    }
+
+    #[test]
+    fn test_generate() {
+        let defaults = serde_json::json!({
+            "cf_api_token": "",
+            "cf_zone_id": "",
+            "config_source": "defaults",
+            "dns_record_name": "",
+            "targets": [],
+            "ttl": 120,
+            "update_interval": null,
+            "webhook_url": "",
+            "compare": "content",
+            "fallback_record_type": null,
+            "platform_identifier": "",
+        });
+        let sample = generate(&defaults, &command());
+        assert!(sample.starts_with("# cloudflare-ddns "));
+        // 必填项不注释，便于直接填写
+        assert!(sample.contains("# --- Required ---\n\n# Cloudflare API token\ncf_api_token = \"\"\n\ncf_zone_id = \"\"\n"));
+        assert!(sample.contains("\n# DNS record TTL\nttl = 120\n"));
+        assert!(sample.contains("\n# Update interval\n# update_interval =\n"));
+        // 选项名与配置键不同时按参数 ID 匹配，没有选项的键使用内置说明
+        assert!(sample.contains("\n# Fallback record type\n# fallback_record_type =\n"));
+        assert!(sample.contains("\n# Host identifier used in logs and notifications"));
+        assert!(sample.contains("\n# Webhook notified on changes\n# webhook_url = \"\"\n"));
+        assert!(sample.contains("\ncompare = \"content\"\n"));
+        assert!(!sample.contains("config_source"));
+        assert!(!sample.contains("targets = []"));
+        assert!(sample.ends_with(SECTIONS));
+    }
+}
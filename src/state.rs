@@ -0,0 +1,255 @@
+//! Persistent per-record state
+//!
+//! Kept in memory for the life of the process and, when `--state-file` is
+//! set, written back as JSON after every change so it survives restarts.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// What is remembered about one record
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RecordState {
+    /// Last time the record was created or updated by this tool
+    #[serde(default)]
+    pub last_write: Option<DateTime<Utc>>,
+    /// Content of that write, compared instead of a lookup when `--record-id` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_content: Option<String>,
+}
+
+/// What is remembered about one domain, across its record types
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DomainState {
+    /// Last time the domain was confirmed or updated to the correct content
+    #[serde(default)]
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct State {
+    #[serde(default)]
+    pub records: HashMap<String, RecordState>,
+    #[serde(default)]
+    pub domains: HashMap<String, DomainState>,
+    /// Whether each IP service last returned an address, keyed by [`service_key`]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub services: HashMap<String, bool>,
+}
+
+/// Key identifying a record in the state: `<zone_id>/<name>/<type>`
+pub fn record_key(zone_id: &str, name: &str, record_type: &str) -> String {
+    format!("{}/{}/{}", zone_id, name, record_type)
+}
+
+/// Key identifying a domain in the state: `<zone_id>/<name>`
+pub fn domain_key(zone_id: &str, name: &str) -> String {
+    format!("{}/{}", zone_id, name)
+}
+
+/// Key identifying an IP service for one record type: `<record_type>/<url>`
+pub fn service_key(record_type: &str, url: &str) -> String {
+    format!("{}/{}", record_type, url)
+}
+
+/// Whether a domain has gone longer than `max_age` without success (never succeeding counts)
+pub fn success_overdue(last_success: Option<DateTime<Utc>>, now: DateTime<Utc>, max_age: Duration) -> bool {
+    if max_age.is_zero() {
+        return false;
+    }
+    match last_success {
+        Some(last) => now.signed_duration_since(last).to_std().unwrap_or_default() > max_age,
+        None => true,
+    }
+}
+
+/// Time left before a record may be written again, if `min_interval` has not elapsed
+pub fn throttle_remaining(last_write: Option<DateTime<Utc>>, now: DateTime<Utc>, min_interval: Duration) -> Option<Duration> {
+    let elapsed = now.signed_duration_since(last_write?).to_std().unwrap_or_default();
+    min_interval.checked_sub(elapsed).filter(|remaining| !remaining.is_zero())
+}
+
+/// State shared by all updates, optionally backed by a file
+#[derive(Debug, Default)]
+pub struct StateStore {
+    path: Option<PathBuf>,
+    state: Mutex<State>,
+    // 是否把 IP 服务的成功记录写入状态文件（否则只在本次运行中生效）
+    persist_services: bool,
+}
+
+impl StateStore {
+    /// Load state from `path`; a missing file starts empty
+    pub fn load(path: &Path) -> Result<Self> {
+        let state = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| anyhow::anyhow!("Invalid state file {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => State::default(),
+            Err(e) => return Err(anyhow::anyhow!("Unable to read state file {}: {}", path.display(), e)),
+        };
+        Ok(Self { path: Some(path.to_path_buf()), state: Mutex::new(state), persist_services: false })
+    }
+
+    /// Keep the IP service order in the state file (`--persist-service-order`)
+    pub fn with_service_order(mut self, persist: bool) -> Self {
+        self.persist_services = persist;
+        if !persist {
+            self.state.get_mut().unwrap().services.clear();
+        }
+        self
+    }
+
+    pub fn record(&self, key: &str) -> RecordState {
+        self.state.lock().unwrap().records.get(key).cloned().unwrap_or_default()
+    }
+
+    pub fn domain(&self, key: &str) -> DomainState {
+        self.state.lock().unwrap().domains.get(key).cloned().unwrap_or_default()
+    }
+
+    /// Apply `change` to a record's state and persist it
+    pub fn update(&self, key: &str, change: impl FnOnce(&mut RecordState)) {
+        let mut state = self.state.lock().unwrap();
+        change(state.records.entry(key.to_string()).or_default());
+        self.persist(&state);
+    }
+
+    /// Apply `change` to a domain's state and persist it
+    pub fn update_domain(&self, key: &str, change: impl FnOnce(&mut DomainState)) {
+        let mut state = self.state.lock().unwrap();
+        change(state.domains.entry(key.to_string()).or_default());
+        self.persist(&state);
+    }
+
+    /// Order `services` for `record_type`: ones that last succeeded first, then untried ones,
+    /// then ones that last failed (otherwise keeping the given order)
+    pub fn ordered_services<'a>(&self, record_type: &str, services: &[&'a str]) -> Vec<&'a str> {
+        let state = self.state.lock().unwrap();
+        let mut ordered = services.to_vec();
+        ordered.sort_by_key(|url| match state.services.get(&service_key(record_type, url)) {
+            Some(true) => 0,
+            None => 1,
+            Some(false) => 2,
+        });
+        ordered
+    }
+
+    /// Remember whether a service returned an address for `record_type`
+    pub fn update_service(&self, record_type: &str, url: &str, succeeded: bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.services.insert(service_key(record_type, url), succeeded) != Some(succeeded) {
+            self.persist(&state);
+        }
+    }
+
+    fn persist(&self, state: &State) {
+        if let Err(e) = self.save(state) {
+            warn!("⚠️ Failed to save state: {}", e);
+        }
+    }
+
+    fn save(&self, state: &State) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        // 先写临时文件再重命名，避免中断时留下半截文件
+        let tmp = path.with_extension("tmp");
+        let mut json = serde_json::to_value(state)?;
+        if let Some(state) = json.as_object_mut().filter(|_| !self.persist_services) {
+            state.remove("services");
+        }
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&json)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_remaining() {
+        let start = Utc::now();
+        let min_interval = Duration::from_secs(60);
+
+        // 从未写入过
+        assert_eq!(throttle_remaining(None, start, min_interval), None);
+        // 间隔未到
+        let now = start + chrono::Duration::seconds(20);
+        assert_eq!(throttle_remaining(Some(start), now, min_interval), Some(Duration::from_secs(40)));
+        // 间隔已到
+        let now = start + chrono::Duration::seconds(60);
+        assert_eq!(throttle_remaining(Some(start), now, min_interval), None);
+        // 未启用
+        assert_eq!(throttle_remaining(Some(start), start, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_state_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let key = record_key("zone", "home.example.com", "A");
+        let written = Utc::now();
+
+        let store = StateStore::load(&path).unwrap();
+        assert_eq!(store.record(&key), RecordState::default());
+        store.update(&key, |record| record.last_write = Some(written));
+
+        let reloaded = StateStore::load(&path).unwrap();
+        assert_eq!(reloaded.record(&key).last_write, Some(written));
+
+        // 域名成功时间与记录写入时间互不影响
+        let domain = domain_key("zone", "home.example.com");
+        reloaded.update_domain(&domain, |state| state.last_success = Some(written));
+        let reloaded = StateStore::load(&path).unwrap();
+        assert_eq!(reloaded.domain(&domain).last_success, Some(written));
+        assert_eq!(reloaded.record(&key).last_write, Some(written));
+    }
+
+    #[test]
+    fn test_service_order() {
+        let services = ["https://v4-only.example", "https://dual.example", "https://other.example"];
+        let store = StateStore::default();
+        assert_eq!(store.ordered_services("AAAA", &services), services);
+
+        // 只支持 IPv4 的服务失败后排到最后，成功的服务排到最前
+        store.update_service("AAAA", "https://v4-only.example", false);
+        store.update_service("AAAA", "https://other.example", true);
+        assert_eq!(
+            store.ordered_services("AAAA", &services),
+            ["https://other.example", "https://dual.example", "https://v4-only.example"]
+        );
+        // 各记录类型分别记录
+        assert_eq!(store.ordered_services("A", &services), services);
+
+        // 仅在 --persist-service-order 时写入状态文件
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        StateStore::load(&path).unwrap().with_service_order(true).update_service("AAAA", "https://v4-only.example", false);
+        let reloaded = StateStore::load(&path).unwrap().with_service_order(true);
+        assert_eq!(reloaded.ordered_services("AAAA", &services)[2], "https://v4-only.example");
+        let reloaded = StateStore::load(&path).unwrap().with_service_order(false);
+        assert_eq!(reloaded.ordered_services("AAAA", &services), services);
+        reloaded.update_service("AAAA", "https://dual.example", true);
+        reloaded.update(&record_key("zone", "home.example.com", "A"), |record| record.last_write = Some(Utc::now()));
+        assert!(StateStore::load(&path).unwrap().with_service_order(true).state.lock().unwrap().services.is_empty());
+    }
+
+    #[test]
+    fn test_success_overdue() {
+        let now = Utc::now();
+        let max_age = Duration::from_secs(3600);
+
+        assert!(success_overdue(None, now, max_age));
+        assert!(!success_overdue(Some(now - chrono::Duration::seconds(600)), now, max_age));
+        assert!(success_overdue(Some(now - chrono::Duration::seconds(7200)), now, max_age));
+        // 未启用
+        assert!(!success_overdue(None, now, Duration::ZERO));
+    }
+}
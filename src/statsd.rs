@@ -0,0 +1,131 @@
+//! StatsD metrics over UDP (`--statsd-addr`)
+//!
+//! After every update cycle a single datagram with counters and gauges is
+//! sent to a StatsD (or Datadog) agent. Sending is fire-and-forget: the socket
+//! is non-blocking and errors are only logged at debug level, so a missing
+//! agent never slows the update loop. Sending is only built with the
+//! `metrics` feature; other builds accept the option and warn at startup.
+
+use anyhow::Result;
+use log::debug;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::OnceLock;
+
+/// Whether this build can send metrics
+pub const SUPPORTED: bool = cfg!(feature = "metrics");
+
+/// Prefix of every metric name
+const PREFIX: &str = "cloudflare_ddns";
+
+/// One StatsD metric
+#[derive(Debug, Clone, PartialEq)]
+pub enum Metric {
+    Counter(&'static str, u64),
+    Gauge(&'static str, f64),
+    /// Gauge with DogStatsD tags (`|#key:value,...`)
+    TaggedGauge(&'static str, f64, Vec<(&'static str, String)>),
+}
+
+impl Metric {
+    /// The metric in StatsD line format, e.g. `cloudflare_ddns.updates:2|c`
+    pub fn line(&self) -> String {
+        match self {
+            Self::Counter(name, value) => format!("{}.{}:{}|c", PREFIX, name, value),
+            Self::Gauge(name, value) => format!("{}.{}:{}|g", PREFIX, name, value),
+            Self::TaggedGauge(name, value, tags) => {
+                let tags: Vec<String> = tags.iter().map(|(key, value)| format!("{}:{}", key, value)).collect();
+                format!("{}.{}:{}|g|#{}", PREFIX, name, value, tags.join(","))
+            }
+        }
+    }
+}
+
+/// Check a `host:port` agent address without resolving it
+pub fn validate_addr(spec: &str) -> Result<()> {
+    let (host, port) = spec
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow::anyhow!("StatsD address must be host:port, got {}", spec))?;
+    if host.is_empty() || port.parse::<u16>().is_err() {
+        return Err(anyhow::anyhow!("StatsD address must be host:port, got {}", spec));
+    }
+    Ok(())
+}
+
+struct StatsdSink {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl StatsdSink {
+    fn connect(addr: &str) -> Result<Self> {
+        let target = addr
+            .to_socket_addrs()
+            .map_err(|e| anyhow::anyhow!("Unable to resolve StatsD address {}: {}", addr, e))?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("StatsD address {} has no IP address", addr))?;
+        let local: SocketAddr = if target.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" }.parse()?;
+        let socket = UdpSocket::bind(local)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket, target })
+    }
+
+    fn send(&self, metrics: &[Metric]) {
+        let packet = metrics.iter().map(Metric::line).collect::<Vec<_>>().join("\n");
+        if let Err(e) = self.socket.send_to(packet.as_bytes(), self.target) {
+            debug!("Failed to send StatsD metrics to {}: {}", self.target, e);
+        }
+    }
+}
+
+static SINK: OnceLock<StatsdSink> = OnceLock::new();
+
+/// Resolve the agent address and open the socket; metrics are dropped until this succeeds
+pub fn init(addr: &str) -> Result<()> {
+    if !SUPPORTED {
+        return Err(anyhow::anyhow!("this build does not include metrics (build with --features metrics)"));
+    }
+    let sink = StatsdSink::connect(addr)?;
+    let _ = SINK.set(sink);
+    Ok(())
+}
+
+/// Send `metrics` in one datagram (no-op without `init`)
+pub fn send(metrics: &[Metric]) {
+    if let Some(sink) = SINK.get() {
+        sink.send(metrics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_line() {
+        assert_eq!(Metric::Counter("updates", 2).line(), "cloudflare_ddns.updates:2|c");
+        assert_eq!(Metric::Gauge("last_update_age", 12.5).line(), "cloudflare_ddns.last_update_age:12.5|g");
+        let info = Metric::TaggedGauge("current_ip_info", 1.0, vec![("record_type", "A".to_string()), ("ip", "203.0.113.7".to_string())]);
+        assert_eq!(info.line(), "cloudflare_ddns.current_ip_info:1|g|#record_type:A,ip:203.0.113.7");
+    }
+
+    #[test]
+    fn test_validate_addr() {
+        assert!(validate_addr("127.0.0.1:8125").is_ok());
+        assert!(validate_addr("statsd.local:8125").is_ok());
+        assert!(validate_addr("statsd.local").is_err());
+        assert!(validate_addr(":8125").is_err());
+        assert!(validate_addr("statsd.local:port").is_err());
+    }
+
+    #[test]
+    fn test_send() {
+        let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        agent.set_read_timeout(Some(std::time::Duration::from_secs(2))).unwrap();
+        let sink = StatsdSink::connect(&agent.local_addr().unwrap().to_string()).unwrap();
+        sink.send(&[Metric::Counter("updates", 1), Metric::Gauge("last_update_age", 0.0)]);
+
+        let mut buf = [0u8; 512];
+        let len = agent.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"cloudflare_ddns.updates:1|c\ncloudflare_ddns.last_update_age:0|g");
+    }
+}
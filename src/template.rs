@@ -0,0 +1,164 @@
+//! Status message templates
+//!
+//! Per-domain status lines can be customized (e.g. localized or shaped for a
+//! log parser) with `{placeholder}` templates. `{{` and `}}` produce literal
+//! braces.
+
+use anyhow::Result;
+
+/// Placeholders available in every template
+pub const PLACEHOLDERS: [&str; 5] = ["domain", "old_ip", "new_ip", "timestamp", "error"];
+
+/// Events with a customizable status line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageEvent {
+    Updated,
+    Created,
+    Unchanged,
+    Failed,
+}
+
+impl MessageEvent {
+    pub const ALL: [MessageEvent; 4] = [Self::Updated, Self::Created, Self::Unchanged, Self::Failed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Updated => "updated",
+            Self::Created => "created",
+            Self::Unchanged => "unchanged",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Default template, matching the built-in status line
+    pub fn default_template(self) -> &'static str {
+        match self {
+            Self::Updated => "{timestamp} - DNS record {domain} updated to {new_ip}",
+            Self::Created => "{timestamp} - DNS record {domain} added successfully",
+            Self::Unchanged => "{timestamp} - IP not changed ({new_ip}) for {domain}",
+            Self::Failed => "{timestamp} - {error}",
+        }
+    }
+}
+
+/// Values substituted into a template; unset ones render empty
+#[derive(Debug, Default)]
+pub struct MessageFields<'a> {
+    pub domain: &'a str,
+    pub old_ip: &'a str,
+    pub new_ip: &'a str,
+    pub timestamp: &'a str,
+    pub error: &'a str,
+}
+
+impl MessageFields<'_> {
+    fn get(&self, name: &str) -> &str {
+        match name {
+            "domain" => self.domain,
+            "old_ip" => self.old_ip,
+            "new_ip" => self.new_ip,
+            "timestamp" => self.timestamp,
+            "error" => self.error,
+            _ => "",
+        }
+    }
+}
+
+/// A piece of a parsed template
+enum Token<'a> {
+    Text(&'a str),
+    Brace(char),
+    Placeholder(&'a str),
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        if pos > 0 {
+            tokens.push(Token::Text(&rest[..pos]));
+        }
+        let brace = rest.as_bytes()[pos] as char;
+        let after = &rest[pos + 1..];
+        if after.starts_with(brace) {
+            tokens.push(Token::Brace(brace));
+            rest = &after[1..];
+            continue;
+        }
+        if brace == '}' {
+            return Err(anyhow::anyhow!("Unmatched '}}' in message template: {}", template));
+        }
+        let Some(end) = after.find('}') else {
+            return Err(anyhow::anyhow!("Unclosed '{{' in message template: {}", template));
+        };
+        let name = &after[..end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(anyhow::anyhow!(
+                "Unknown placeholder {{{}}} in message template (available: {})",
+                name, PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+            ));
+        }
+        tokens.push(Token::Placeholder(name));
+        rest = &after[end + 1..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
+
+/// Check that `template` only uses known placeholders and balanced braces
+pub fn validate(template: &str) -> Result<()> {
+    tokenize(template).map(|_| ())
+}
+
+/// Render `template`; an invalid template is returned unchanged
+pub fn render(template: &str, fields: &MessageFields) -> String {
+    let Ok(tokens) = tokenize(template) else {
+        return template.to_string();
+    };
+    let mut message = String::new();
+    for token in tokens {
+        match token {
+            Token::Text(text) => message.push_str(text),
+            Token::Brace(brace) => message.push(brace),
+            Token::Placeholder(name) => message.push_str(fields.get(name)),
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let fields = MessageFields {
+            domain: "home.example.com",
+            old_ip: "203.0.113.1",
+            new_ip: "203.0.113.2",
+            timestamp: "2024-01-01 00:00:00",
+            ..Default::default()
+        };
+        assert_eq!(
+            render(MessageEvent::Updated.default_template(), &fields),
+            "2024-01-01 00:00:00 - DNS record home.example.com updated to 203.0.113.2"
+        );
+        assert_eq!(
+            render("{domain}: {old_ip} => {new_ip} {{ok}}", &fields),
+            "home.example.com: 203.0.113.1 => 203.0.113.2 {ok}"
+        );
+        assert_eq!(render("失败 {error}", &fields), "失败 ");
+    }
+
+    #[test]
+    fn test_validate() {
+        for event in MessageEvent::ALL {
+            assert!(validate(event.default_template()).is_ok());
+        }
+        assert!(validate("{ip}").is_err());
+        assert!(validate("{domain").is_err());
+        assert!(validate("domain}").is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! `--tui` live dashboard (built with the `tui` feature)
+//!
+//! Rendering runs on its own thread and is fed through the `dashboard`
+//! channel, so the update loop never waits on the terminal.
+
+use crate::dashboard::{self, Event};
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::mpsc::Receiver;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Log lines kept for the log pane
+const MAX_LOG_LINES: usize = 200;
+
+static RENDER_THREAD: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+struct DomainRow {
+    status: String,
+    ok: bool,
+    at: DateTime<Local>,
+}
+
+#[derive(Default)]
+struct Dashboard {
+    current_ip: Option<String>,
+    domains: BTreeMap<String, DomainRow>,
+    next_update: Option<Instant>,
+    logs: VecDeque<String>,
+}
+
+impl Dashboard {
+    fn apply(&mut self, event: Event) {
+        match event {
+            Event::Log(line) => {
+                if self.logs.len() == MAX_LOG_LINES {
+                    self.logs.pop_front();
+                }
+                self.logs.push_back(line);
+            }
+            Event::CurrentIp(ip) => self.current_ip = Some(ip),
+            Event::Domain { name, status, ok } => {
+                self.domains.insert(name, DomainRow { status, ok, at: Local::now() });
+            }
+            Event::NextUpdate(at) => self.next_update = Some(at),
+            Event::Quit => {}
+        }
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [header, domains, logs] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(self.domains.len() as u16 + 3),
+            Constraint::Fill(1),
+        ])
+        .areas(frame.area());
+
+        let next_update = match self.next_update {
+            Some(at) => format!("{}s", at.saturating_duration_since(Instant::now()).as_secs()),
+            None => "-".to_string(),
+        };
+        let summary = format!(
+            "Current IP: {}   Next update in: {}   (q to quit)",
+            self.current_ip.as_deref().unwrap_or("-"),
+            next_update
+        );
+        frame.render_widget(
+            Paragraph::new(summary).block(Block::bordered().title(" cloudflare-ddns ")),
+            header,
+        );
+
+        let rows = self.domains.iter().map(|(name, row)| {
+            let color = if row.ok { Color::Green } else { Color::Red };
+            Row::new(vec![name.clone(), row.status.clone(), row.at.format("%H:%M:%S").to_string()])
+                .style(Style::default().fg(color))
+        });
+        let widths = [Constraint::Percentage(45), Constraint::Percentage(40), Constraint::Percentage(15)];
+        frame.render_widget(
+            Table::new(rows, widths)
+                .header(Row::new(vec!["Domain", "Status", "Updated"]).style(Style::default().fg(Color::Yellow)))
+                .block(Block::bordered().title(" Domains ")),
+            domains,
+        );
+
+        let visible = logs.height.saturating_sub(2) as usize;
+        let lines = self.logs.iter().skip(self.logs.len().saturating_sub(visible)).map(|line| Line::raw(line.as_str()));
+        frame.render_widget(List::new(lines).block(Block::bordered().title(" Log ")), logs);
+    }
+}
+
+/// Take over the terminal and start rendering dashboard events
+pub fn start() -> Result<()> {
+    let events = dashboard::channel().ok_or_else(|| anyhow::anyhow!("Dashboard already started"))?;
+    let terminal = ratatui::try_init()?;
+    let handle = std::thread::spawn(move || run(terminal, events));
+    *RENDER_THREAD.lock().unwrap() = Some(handle);
+    Ok(())
+}
+
+/// Stop rendering and restore the terminal (no-op if the dashboard is not running)
+pub fn stop() {
+    let Some(handle) = RENDER_THREAD.lock().unwrap().take() else {
+        return;
+    };
+    dashboard::send(Event::Quit);
+    let _ = handle.join();
+}
+
+fn run(mut terminal: DefaultTerminal, events: Receiver<Event>) {
+    let mut dashboard = Dashboard::default();
+    loop {
+        while let Ok(event) = events.try_recv() {
+            if matches!(event, Event::Quit) {
+                ratatui::restore();
+                return;
+            }
+            dashboard.apply(event);
+        }
+        let _ = terminal.draw(|frame| dashboard.render(frame));
+
+        // 原始模式下 Ctrl-C 不再产生 SIGINT，需在此处理退出按键
+        if !event::poll(Duration::from_millis(250)).unwrap_or(false) {
+            continue;
+        }
+        if let Ok(TermEvent::Key(key)) = event::read() {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind == KeyEventKind::Press && (key.code == KeyCode::Char('q') || ctrl_c) {
+                ratatui::restore();
+                std::process::exit(0);
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! TXT record content quoting and chunking
+//!
+//! A single DNS character-string holds at most 255 bytes, so longer values
+//! (DKIM keys, long SPF policies) are sent to Cloudflare as several quoted
+//! strings separated by spaces, which resolvers concatenate back together.
+
+use anyhow::Result;
+
+/// Maximum bytes in one character-string
+pub const MAX_CHUNK_BYTES: usize = 255;
+
+/// Maximum TXT content length Cloudflare accepts
+pub const MAX_CONTENT_BYTES: usize = 2048;
+
+/// Strip surrounding quotes and join quoted chunks back into the raw value
+pub fn unquote(content: &str) -> String {
+    let trimmed = content.trim();
+    if !trimmed.starts_with('"') {
+        return trimmed.to_string();
+    }
+
+    let mut value = String::new();
+    let mut in_quotes = false;
+    let mut chars = trimmed.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => value.extend(chars.next()),
+            _ if in_quotes => value.push(c),
+            _ => {}
+        }
+    }
+    value
+}
+
+/// Split `value` into chunks of at most 255 bytes without breaking UTF-8 characters
+pub fn chunks(value: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = value;
+    while rest.len() > MAX_CHUNK_BYTES {
+        let mut end = MAX_CHUNK_BYTES;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Normalize a TXT value into the content sent to Cloudflare: values over 255 bytes
+/// become space-separated quoted chunks, shorter ones are sent unquoted
+pub fn normalize(value: &str) -> Result<String> {
+    let raw = unquote(value);
+    if raw.is_empty() {
+        return Err(anyhow::anyhow!("TXT value must not be empty"));
+    }
+    if raw.len() > MAX_CONTENT_BYTES {
+        return Err(anyhow::anyhow!(
+            "TXT value is {} bytes, Cloudflare accepts at most {}",
+            raw.len(), MAX_CONTENT_BYTES
+        ));
+    }
+    if raw.len() <= MAX_CHUNK_BYTES {
+        return Ok(raw);
+    }
+
+    Ok(chunks(&raw)
+        .iter()
+        .map(|chunk| format!("\"{}\"", chunk.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_value_unquoted() {
+        assert_eq!(normalize("\"v=spf1 -all\"").unwrap(), "v=spf1 -all");
+        assert_eq!(normalize("v=spf1 -all").unwrap(), "v=spf1 -all");
+        assert!(normalize("\"\"").is_err());
+    }
+
+    #[test]
+    fn test_long_value_chunked() {
+        let value = format!("v=DKIM1; k=rsa; p={}", "A".repeat(400));
+        let content = normalize(&value).unwrap();
+
+        let parts: Vec<&str> = content.split("\" \"").collect();
+        assert_eq!(parts.len(), 2);
+        assert!(content.starts_with('"') && content.ends_with('"'));
+        assert_eq!(parts[0].len() - 1, MAX_CHUNK_BYTES);
+
+        // 往返后得到原值
+        assert_eq!(unquote(&content), value);
+        assert_eq!(normalize(&content).unwrap(), content);
+
+        assert!(normalize(&"A".repeat(MAX_CONTENT_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn test_chunks_respect_utf8_boundaries() {
+        let value = "é".repeat(200);
+        let chunks = chunks(&value);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_CHUNK_BYTES));
+        assert_eq!(chunks.concat(), value);
+    }
+}
@@ -0,0 +1,229 @@
+//! Event-driven updates on interface address changes (`--watch-interface`)
+//!
+//! On Linux the watcher subscribes to netlink address notifications and
+//! reports a change only when the interface's usable address set actually
+//! differs from the last one seen. Bursts of changes (a flapping link) are
+//! coalesced with `--event-coalesce`, so only the settled address set
+//! triggers an update. Other platforms fall back to polling.
+
+use crate::clock::{Clock, SystemClock};
+use crate::interface;
+use anyhow::Result;
+use log::{debug, warn};
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+pub struct AddressWatcher<C: Clock = SystemClock> {
+    interface: String,
+    // 上次触发更新时的地址集合；一阵变化平息后与之相同（地址来回抖动）则不触发
+    reported: BTreeSet<IpAddr>,
+    changes: Debounced<BTreeSet<IpAddr>, C>,
+}
+
+impl<C: Clock> AddressWatcher<C> {
+    /// Start listening for address changes on `interface`; changes less than `coalesce`
+    /// apart (measured on `clock`) are reported once, after they settle
+    #[cfg(target_os = "linux")]
+    pub fn new(interface: &str, coalesce: Duration, clock: C) -> Result<Self> {
+        use futures::StreamExt;
+        use netlink_sys::{AsyncSocket, SocketAddr};
+        use rtnetlink::constants::{RTMGRP_IPV4_IFADDR, RTMGRP_IPV6_IFADDR};
+
+        let (mut connection, _, messages) = rtnetlink::new_connection()?;
+        connection
+            .socket_mut()
+            .socket_mut()
+            .bind(&SocketAddr::new(0, RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR))?;
+        tokio::spawn(connection);
+
+        let current = interface::usable_addresses(interface)?;
+        let mut monitor = AddressMonitor {
+            interface: interface.to_string(),
+            last: current.clone(),
+            events: messages.map(|_| ()).boxed(),
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while tx.send(monitor.changed().await).is_ok() {}
+        });
+
+        Ok(Self::from_channel(interface, current, rx, coalesce, clock))
+    }
+
+    /// Watch address sets received on `rx`, starting from `current`
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn from_channel(interface: &str, current: BTreeSet<IpAddr>, rx: mpsc::UnboundedReceiver<BTreeSet<IpAddr>>, coalesce: Duration, clock: C) -> Self {
+        Self {
+            interface: interface.to_string(),
+            reported: current,
+            changes: Debounced::new(rx, coalesce, clock),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn new(_interface: &str, _coalesce: Duration, _clock: C) -> Result<Self> {
+        Err(anyhow::anyhow!("Interface watching is only supported on Linux"))
+    }
+
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// Wait until the interface's usable addresses settle on a set different from the last reported one
+    pub async fn changed(&mut self) -> BTreeSet<IpAddr> {
+        loop {
+            let Some((settled, events)) = self.changes.recv().await else {
+                // 监听任务已结束，仅依赖安全轮询
+                return std::future::pending().await;
+            };
+            if events > 1 {
+                debug!("Coalesced {} address changes on {}", events, self.interface);
+            }
+            if settled != self.reported {
+                self.reported = settled.clone();
+                return settled;
+            }
+            debug!("Addresses of {} settled back to {:?}, no update", self.interface, settled);
+        }
+    }
+}
+
+/// Follows netlink notifications and reads the interface's addresses after each one
+#[cfg(target_os = "linux")]
+struct AddressMonitor {
+    interface: String,
+    last: BTreeSet<IpAddr>,
+    events: futures::stream::BoxStream<'static, ()>,
+}
+
+#[cfg(target_os = "linux")]
+impl AddressMonitor {
+    /// Wait until the interface's usable addresses differ from the last seen set
+    async fn changed(&mut self) -> BTreeSet<IpAddr> {
+        loop {
+            self.next_event().await;
+            match interface::usable_addresses(&self.interface) {
+                Ok(current) if current != self.last => {
+                    self.last = current.clone();
+                    return current;
+                }
+                Ok(_) => debug!("Address event on {} without change", self.interface),
+                Err(e) => warn!("⚠️ Failed to read addresses of {}: {}", self.interface, e),
+            }
+        }
+    }
+
+    async fn next_event(&mut self) {
+        use futures::StreamExt;
+        if self.events.next().await.is_none() {
+            // netlink 连接已关闭，仅依赖安全轮询
+            warn!("⚠️ Address notifications for {} stopped, relying on safety poll", self.interface);
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Receives values from a channel, coalescing each burst into its last value
+pub struct Debounced<T, C: Clock = SystemClock> {
+    rx: mpsc::UnboundedReceiver<T>,
+    window: Duration,
+    clock: C,
+}
+
+impl<T, C: Clock> Debounced<T, C> {
+    pub fn new(rx: mpsc::UnboundedReceiver<T>, window: Duration, clock: C) -> Self {
+        Self { rx, window, clock }
+    }
+
+    /// Wait for a value, then keep taking newer ones until `window` passes without one.
+    /// Returns the last value and how many were coalesced; `None` once the sender is gone
+    pub async fn recv(&mut self) -> Option<(T, usize)> {
+        let mut last = self.rx.recv().await?;
+        let mut count = 1;
+        loop {
+            tokio::select! {
+                value = self.rx.recv() => match value {
+                    Some(value) => {
+                        last = value;
+                        count += 1;
+                    }
+                    None => break,
+                },
+                _ = self.clock.sleep(self.window) => break,
+            }
+        }
+        Some((last, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Let `duration` of paused time pass and the woken tasks run
+    async fn pass(duration: Duration) {
+        tokio::time::advance(duration).await;
+        tokio::task::yield_now().await;
+    }
+
+    fn addresses(list: &[&str]) -> BTreeSet<IpAddr> {
+        list.iter().map(|ip| ip.parse().unwrap()).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_debounced_coalesces_burst() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut debounced = Debounced::new(rx, Duration::from_millis(50), SystemClock);
+        let receiver = tokio::spawn(async move {
+            let first = debounced.recv().await;
+            (debounced, first)
+        });
+
+        // 链路抖动：短时间内连续多次变化，只触发一次，使用最终地址
+        for i in 1..=5 {
+            tx.send(i).unwrap();
+            pass(Duration::from_millis(5)).await;
+        }
+        assert!(!receiver.is_finished());
+        pass(Duration::from_millis(50)).await;
+        assert!(receiver.is_finished());
+        let (mut debounced, first) = receiver.await.unwrap();
+        assert_eq!(first, Some((5, 5)));
+
+        // 平息之后的变化单独触发
+        tx.send(6).unwrap();
+        drop(tx);
+        assert_eq!(debounced.recv().await, Some((6, 1)));
+        assert_eq!(debounced.recv().await, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_address_watcher_flapping() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher = AddressWatcher::from_channel("eth0", addresses(&["192.0.2.1"]), rx, Duration::from_millis(50), SystemClock);
+        let waiter = tokio::spawn(async move {
+            let settled = watcher.changed().await;
+            (watcher, settled)
+        });
+
+        // 地址来回抖动后回到原值，不触发更新
+        tx.send(addresses(&["192.0.2.2"])).unwrap();
+        pass(Duration::from_millis(5)).await;
+        tx.send(addresses(&["192.0.2.1"])).unwrap();
+        pass(Duration::from_millis(60)).await;
+        assert!(!waiter.is_finished());
+
+        // 一阵变化后停在新地址上，只触发一次
+        for ip in ["192.0.2.2", "192.0.2.3", "198.51.100.4"] {
+            tx.send(addresses(&[ip])).unwrap();
+            pass(Duration::from_millis(5)).await;
+        }
+        pass(Duration::from_millis(50)).await;
+        assert!(waiter.is_finished());
+        let (mut watcher, settled) = waiter.await.unwrap();
+        assert_eq!(settled, addresses(&["198.51.100.4"]));
+        assert!(tokio::time::timeout(Duration::from_secs(60), watcher.changed()).await.is_err());
+    }
+}
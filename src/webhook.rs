@@ -0,0 +1,99 @@
+//! Webhook notifications on IP change
+//!
+//! When a secret is configured the JSON body is signed with HMAC-SHA256 and
+//! the hex digest is sent as `X-DDNS-Signature: sha256=<hex>`, so receivers
+//! can verify the notification came from this instance. Delivery is only
+//! built with the `notify` feature; other builds accept the option and warn once.
+
+#[cfg(feature = "notify")]
+use anyhow::Result;
+#[cfg(feature = "notify")]
+use hmac::{Hmac, Mac};
+#[cfg(feature = "notify")]
+use log::debug;
+use log::warn;
+#[cfg(feature = "notify")]
+use sha2::Sha256;
+#[cfg(feature = "notify")]
+use std::time::Duration;
+
+/// Whether this build can send webhook notifications
+pub const SUPPORTED: bool = cfg!(feature = "notify");
+
+/// Header carrying the HMAC-SHA256 signature of the request body
+#[cfg(feature = "notify")]
+pub const SIGNATURE_HEADER: &str = "X-DDNS-Signature";
+
+/// Compute the `sha256=<hex>` signature of `body` using `secret`
+#[cfg(feature = "notify")]
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Build the payload describing a record whose IP changed
+pub fn ip_change_payload(domain: &str, record_type: &str, old_ip: Option<&str>, new_ip: &str, timestamp: &str) -> serde_json::Value {
+    serde_json::json!({
+        "event": "ip_changed",
+        "domain": domain,
+        "record_type": record_type,
+        "old_ip": old_ip,
+        "new_ip": new_ip,
+        "timestamp": timestamp,
+    })
+}
+
+/// POST `payload` to `url`, signing it when `secret` is set
+#[cfg(feature = "notify")]
+pub async fn send(client: &reqwest::Client, url: &str, secret: Option<&str>, payload: &serde_json::Value) -> Result<()> {
+    let body = serde_json::to_vec(payload)?;
+
+    let mut request = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .timeout(Duration::from_secs(10));
+    if let Some(secret) = secret.filter(|s| !s.is_empty()) {
+        request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+    }
+
+    let response = request.body(body).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Webhook returned HTTP {}", response.status()));
+    }
+    debug!("Webhook delivered to {}", url);
+    Ok(())
+}
+
+/// Warn (once) that this build cannot send webhooks
+pub fn warn_unsupported() {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    if !SUPPORTED {
+        WARNED.call_once(|| warn!("⚠️ This build does not include webhook support (build with --features notify), webhook notifications disabled"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn test_sign_matches_rfc4231_vector() {
+        // RFC 4231 test case 2
+        let signature = sign("Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            signature,
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_ip_change_payload() {
+        let payload = ip_change_payload("home.example.com", "A", None, "1.2.3.4", "2024-01-01 00:00:00");
+        assert_eq!(payload["event"], "ip_changed");
+        assert_eq!(payload["old_ip"], serde_json::Value::Null);
+        assert_eq!(payload["new_ip"], "1.2.3.4");
+    }
+}
@@ -0,0 +1,44 @@
+//! Zone inference from record names
+//!
+//! When neither a zone ID nor a zone name is configured, the zone is taken to
+//! be the registrable domain (eTLD+1) of the first record name, looked up in
+//! a bundled copy of the Public Suffix List (https://publicsuffix.org/list/).
+
+use anyhow::Result;
+use publicsuffix::{List, Psl};
+use std::sync::OnceLock;
+
+static LIST: OnceLock<List> = OnceLock::new();
+
+fn list() -> &'static List {
+    LIST.get_or_init(|| {
+        include_str!("public_suffix_list.dat")
+            .parse()
+            .expect("bundled public suffix list is valid")
+    })
+}
+
+/// Registrable domain of `name`, e.g. `example.co.uk` for `home.example.co.uk`
+pub fn registrable_domain(name: &str) -> Result<String> {
+    let normalized = name.trim().trim_end_matches('.').to_ascii_lowercase();
+    list()
+        .domain(normalized.as_bytes())
+        .and_then(|domain| std::str::from_utf8(domain.as_bytes()).ok().map(str::to_string))
+        .ok_or_else(|| anyhow::anyhow!("Unable to infer the zone of {}: no registrable domain under a public suffix", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registrable_domain() {
+        assert_eq!(registrable_domain("home.example.com").unwrap(), "example.com");
+        assert_eq!(registrable_domain("home.example.co.uk").unwrap(), "example.co.uk");
+        assert_eq!(registrable_domain("a.b.Example.COM.").unwrap(), "example.com");
+        assert_eq!(registrable_domain("example.com").unwrap(), "example.com");
+        // 公共后缀本身或相对名称无法推断
+        assert!(registrable_domain("co.uk").is_err());
+        assert!(registrable_domain("home").is_err());
+    }
+}